@@ -9,9 +9,39 @@ use aoc_2022::day_6;
 use aoc_2022::day_7;
 use aoc_2022::day_8;
 use aoc_2022::day_9;
+use aoc_2022::grid::Grid;
 use criterion::criterion_group;
 use criterion::criterion_main;
-use criterion::{black_box, Criterion};
+use criterion::measurement::WallTime;
+use criterion::{black_box, BenchmarkGroup, Criterion};
+use std::collections::HashSet;
+use std::fmt::Debug;
+
+// times parsing and solving each part on their own, with the model only parsed once for the solves
+fn benchmark_phases<M, A: PartialEq + Debug, B: PartialEq + Debug>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    input: &str,
+    parse: impl Fn(&str) -> anyhow::Result<M>,
+    solve_part_one: impl Fn(&M) -> anyhow::Result<A>,
+    solve_part_two: impl Fn(&M) -> anyhow::Result<B>,
+    (expected_one, expected_two): (A, B),
+) {
+    group.bench_function("Parse", |b| b.iter(|| parse(black_box(input)).unwrap()));
+
+    let model = parse(input).unwrap();
+
+    // the answers are checked once up front, so that only solving is timed
+    assert_eq!(solve_part_one(&model).unwrap(), expected_one);
+    assert_eq!(solve_part_two(&model).unwrap(), expected_two);
+
+    group.bench_function("Solve Part One", |b| {
+        b.iter(|| black_box(solve_part_one(black_box(&model)).unwrap()))
+    });
+
+    group.bench_function("Solve Part Two", |b| {
+        b.iter(|| black_box(solve_part_two(black_box(&model)).unwrap()))
+    });
+}
 
 // DAY 1
 fn benchmark_day_1(c: &mut Criterion) {
@@ -30,6 +60,15 @@ fn benchmark_day_1(c: &mut Criterion) {
             assert_eq!(result, 206_780);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_1::INPUT,
+        day_1::parse,
+        |elves| day_1::solve_part_one(elves),
+        |elves| day_1::solve_part_two(elves),
+        (69_626, 206_780),
+    );
 }
 
 criterion_group!(day_01, benchmark_day_1);
@@ -51,6 +90,15 @@ fn benchmark_day_2(c: &mut Criterion) {
             assert_eq!(result, 8_295);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_2::INPUT,
+        day_2::parse,
+        |rounds| day_2::solve_part_one(rounds),
+        |rounds| day_2::solve_part_two(rounds),
+        (11_150, 8_295),
+    );
 }
 
 criterion_group!(day_02, benchmark_day_2);
@@ -72,6 +120,15 @@ fn benchmark_day_3(c: &mut Criterion) {
             assert_eq!(result, 2_825);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_3::INPUT,
+        day_3::parse,
+        |rucksacks| day_3::solve_part_one(rucksacks),
+        |rucksacks| day_3::solve_part_two(rucksacks),
+        (7_889, 2_825),
+    );
 }
 
 criterion_group!(day_03, benchmark_day_3);
@@ -93,6 +150,15 @@ fn benchmark_day_4(c: &mut Criterion) {
             assert_eq!(result, 905);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_4::INPUT,
+        day_4::parse,
+        |pairs| day_4::solve_part_one(pairs),
+        |pairs| day_4::solve_part_two(pairs),
+        (576, 905),
+    );
 }
 
 criterion_group!(day_04, benchmark_day_4);
@@ -114,6 +180,15 @@ fn benchmark_day_5(c: &mut Criterion) {
             assert_eq!(result, "NHWZCBNBF");
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_5::INPUT,
+        day_5::parse,
+        day_5::solve_part_one,
+        day_5::solve_part_two,
+        ("BWNCQRMDB".to_string(), "NHWZCBNBF".to_string()),
+    );
 }
 
 criterion_group!(day_05, benchmark_day_5);
@@ -135,6 +210,15 @@ fn benchmark_day_6(c: &mut Criterion) {
             assert_eq!(result, 2_260);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_6::INPUT,
+        day_6::parse,
+        |datastream| day_6::solve_part_one(datastream),
+        |datastream| day_6::solve_part_two(datastream),
+        (1_658, 2_260),
+    );
 }
 
 criterion_group!(day_06, benchmark_day_6);
@@ -156,9 +240,74 @@ fn benchmark_day_7(c: &mut Criterion) {
             assert_eq!(result, 8_474_158);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_7::INPUT,
+        day_7::parse,
+        day_7::solve_part_one,
+        day_7::solve_part_two,
+        (1_723_892, 8_474_158),
+    );
+}
+
+// builds a transcript for a single chain of `depth` nested directories, each holding a few files
+fn deep_tree_transcript(depth: usize) -> String {
+    let mut transcript = String::from("$ cd /");
+    for i in 0..depth {
+        transcript.push_str(&format!(
+            "\n$ ls\ndir d{i}\n{} a.txt\n{} b.txt\n$ cd d{i}",
+            i + 1,
+            2 * i + 1
+        ));
+    }
+    transcript.push_str("\n$ ls\n1 leaf.txt");
+
+    transcript
+}
+
+// size every directory by re-summing its whole subtree, as was done before sizes were cached
+fn recursive_size(fs: &day_7::Fs, node: &day_7::FsNode) -> u64 {
+    match node {
+        day_7::FsNode::File(file) => file.size(),
+        day_7::FsNode::Dir(dir) => fs
+            .children(dir)
+            .map(|child| recursive_size(fs, child))
+            .sum(),
+    }
+}
+
+fn benchmark_day_7_deep_tree(c: &mut Criterion) {
+    const DEPTH: usize = 2_000;
+
+    let mut group = c.benchmark_group("Day 7 Deep Tree");
+    let fs = day_7::Fs::from_transcript(&deep_tree_transcript(DEPTH)).unwrap();
+    let cached_sizes = |fs: &day_7::Fs| -> u64 {
+        fs.walk()
+            .filter(|entry| entry.node.is_dir())
+            .map(|entry| entry.node.size())
+            .sum()
+    };
+    let recursive_sizes = |fs: &day_7::Fs| -> u64 {
+        fs.walk()
+            .filter(|entry| entry.node.is_dir())
+            .map(|entry| recursive_size(fs, entry.node))
+            .sum()
+    };
+
+    // both ways of sizing are checked once up front, so that only sizing is timed
+    assert_eq!(cached_sizes(&fs), recursive_sizes(&fs));
+
+    group.bench_function("Cached Sizes", |b| {
+        b.iter(|| black_box(cached_sizes(black_box(&fs))))
+    });
+
+    group.bench_function("Recursive Sizes", |b| {
+        b.iter(|| black_box(recursive_sizes(black_box(&fs))))
+    });
 }
 
-criterion_group!(day_07, benchmark_day_7);
+criterion_group!(day_07, benchmark_day_7, benchmark_day_7_deep_tree);
 
 // DAY 8
 fn benchmark_day_8(c: &mut Criterion) {
@@ -177,6 +326,15 @@ fn benchmark_day_8(c: &mut Criterion) {
             assert_eq!(result, 331_344);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_8::INPUT,
+        day_8::parse,
+        day_8::solve_part_one,
+        day_8::solve_part_two,
+        (1_669, 331_344),
+    );
 }
 
 criterion_group!(day_08, benchmark_day_8);
@@ -198,11 +356,130 @@ fn benchmark_day_9(c: &mut Criterion) {
             assert_eq!(result, 2_405);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_9::INPUT,
+        day_9::parse,
+        |moves| day_9::solve_part_one(moves),
+        |moves| day_9::solve_part_two(moves),
+        (5_878, 2_405),
+    );
+}
+
+fn long_walk_input(num_steps: u32) -> String {
+    // xorshift, so that every run walks the same way
+    let mut state: u32 = 0x9e37_79b9;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+
+    let mut moves = Vec::new();
+    let mut steps = 0;
+    while steps < num_steps {
+        let direction = ["R", "U", "L", "D"][next() as usize % 4];
+        let amount = (next() % 2_000 + 1).min(num_steps - steps);
+        moves.push(format!("{direction} {amount}"));
+        steps += amount;
+    }
+
+    moves.join("\n")
+}
+
+// step every knot one cell at a time, tracking each one's visited cells in a `HashSet` as was done
+// before they were stored as bitmaps and straight ropes were fast-forwarded
+fn hash_set_tail_positions(moves: &[day_9::Move], num_nodes: usize) -> usize {
+    let mut nodes = vec![(0_i32, 0_i32); num_nodes];
+    let mut seen = vec![HashSet::from([(0, 0)]); num_nodes];
+
+    for this_move in moves {
+        let [dx, dy, _] = this_move.direction.offset();
+        for _ in 0..this_move.amount {
+            nodes[0].0 += dx;
+            nodes[0].1 += dy;
+
+            for idx in 1..num_nodes {
+                let (head, tail) = (nodes[idx - 1], &mut nodes[idx]);
+                if (head.0 - tail.0).abs() > 1 || (head.1 - tail.1).abs() > 1 {
+                    tail.0 += (head.0 - tail.0).signum();
+                    tail.1 += (head.1 - tail.1).signum();
+                }
+            }
+
+            for (seen, &node) in seen.iter_mut().zip(&nodes) {
+                seen.insert(node);
+            }
+        }
+    }
+
+    seen[num_nodes - 1].len()
+}
+
+fn benchmark_day_9_long_walk(c: &mut Criterion) {
+    const NUM_STEPS: u32 = 1_000_000;
+
+    let mut group = c.benchmark_group("Day 9 Long Walk");
+    group.sample_size(10);
+
+    let input = long_walk_input(NUM_STEPS);
+    let moves = day_9::parse(&input).unwrap();
+
+    // both ways of following the tail are checked once up front, so that only walking is timed
+    let expected = day_9::part_two(&input).unwrap();
+    assert_eq!(hash_set_tail_positions(&moves, 10), expected as usize);
+
+    group.bench_function("Part One", |b| {
+        b.iter(|| day_9::part_one(black_box(&input)).unwrap())
+    });
+
+    group.bench_function("Part Two", |b| {
+        b.iter(|| day_9::part_two(black_box(&input)).unwrap())
+    });
+
+    group.bench_function("Part Two HashSet", |b| {
+        b.iter(|| hash_set_tail_positions(black_box(&moves), 10))
+    });
 }
 
-criterion_group!(day_09, benchmark_day_9);
+criterion_group!(day_09, benchmark_day_9, benchmark_day_9_long_walk);
 
 // DAY 10
+const EXPECTED_SCREEN: [[bool; day_10::CRT_WIDTH]; day_10::CRT_HEIGHT] = [
+    [
+        true, true, true, false, false, false, true, true, false, false, false, true, true, false,
+        false, true, true, true, true, false, true, false, false, true, false, true, false, false,
+        false, false, true, false, false, true, false, true, true, true, true, false,
+    ],
+    [
+        true, false, false, true, false, true, false, false, true, false, true, false, false, true,
+        false, true, false, false, false, false, true, false, true, false, false, true, false,
+        false, false, false, true, false, false, true, false, true, false, false, false, false,
+    ],
+    [
+        true, true, true, false, false, true, false, false, true, false, true, false, false, false,
+        false, true, true, true, false, false, true, true, false, false, false, true, false, false,
+        false, false, true, true, true, true, false, true, true, true, false, false,
+    ],
+    [
+        true, false, false, true, false, true, true, true, true, false, true, false, false, false,
+        false, true, false, false, false, false, true, false, true, false, false, true, false,
+        false, false, false, true, false, false, true, false, true, false, false, false, false,
+    ],
+    [
+        true, false, false, true, false, true, false, false, true, false, true, false, false, true,
+        false, true, false, false, false, false, true, false, true, false, false, true, false,
+        false, false, false, true, false, false, true, false, true, false, false, false, false,
+    ],
+    [
+        true, true, true, false, false, true, false, false, true, false, false, true, true, false,
+        false, true, true, true, true, false, true, false, false, true, false, true, true, true,
+        true, false, true, false, false, true, false, true, false, false, false, false,
+    ],
+];
+
 fn benchmark_day_10(c: &mut Criterion) {
     let mut group = c.benchmark_group("Day 10");
 
@@ -215,48 +492,19 @@ fn benchmark_day_10(c: &mut Criterion) {
 
     group.bench_function("Part Two", |b| {
         b.iter(|| {
-            const EXPECTED_RESULT: [[bool; day_10::CRT_WIDTH]; day_10::CRT_HEIGHT] = [
-                [
-                    true, true, true, false, false, false, true, true, false, false, false, true,
-                    true, false, false, true, true, true, true, false, true, false, false, true,
-                    false, true, false, false, false, false, true, false, false, true, false, true,
-                    true, true, true, false,
-                ],
-                [
-                    false, false, false, true, false, true, false, false, true, false, true, false,
-                    false, true, false, true, false, false, false, false, true, false, true, false,
-                    false, true, false, false, false, false, true, false, false, true, false, true,
-                    false, false, false, false,
-                ],
-                [
-                    true, true, true, false, false, true, false, false, true, false, true, false,
-                    false, false, false, true, true, true, false, false, true, true, false, false,
-                    false, true, false, false, false, false, true, true, true, true, false, true,
-                    true, true, false, false,
-                ],
-                [
-                    false, false, false, true, false, true, true, true, true, false, true, false,
-                    false, false, false, true, false, false, false, false, true, false, true,
-                    false, false, true, false, false, false, false, true, false, false, true,
-                    false, true, false, false, false, false,
-                ],
-                [
-                    true, false, false, true, false, true, false, false, true, false, true, false,
-                    false, true, false, true, false, false, false, false, true, false, true, false,
-                    false, true, false, false, false, false, true, false, false, true, false, true,
-                    false, false, false, false,
-                ],
-                [
-                    true, true, true, false, false, true, false, false, true, false, false, true,
-                    true, false, false, true, true, true, true, false, true, false, false, true,
-                    false, true, true, true, true, false, true, false, false, true, false, true,
-                    false, false, false, false,
-                ],
-            ];
             let result = day_10::part_two(black_box(day_10::INPUT)).unwrap();
-            assert_eq!(result, EXPECTED_RESULT);
+            assert_eq!(result, Grid::from(EXPECTED_SCREEN));
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_10::INPUT,
+        day_10::parse,
+        |program| day_10::solve_part_one(program),
+        |program| day_10::solve_part_two(program),
+        (16_060, Grid::from(EXPECTED_SCREEN)),
+    );
 }
 
 criterion_group!(day_10, benchmark_day_10);
@@ -278,9 +526,86 @@ fn benchmark_day_11(c: &mut Criterion) {
             assert_eq!(result, 18_170_818_354);
         })
     });
+
+    benchmark_phases(
+        &mut group,
+        day_11::INPUT,
+        day_11::parse,
+        |monkeys| day_11::solve_part_one(monkeys),
+        |monkeys| day_11::solve_part_two(monkeys),
+        (90_294, 18_170_818_354),
+    );
+}
+
+fn benchmark_day_11_simulators(c: &mut Criterion) {
+    const NUM_ROUNDS: u64 = 10_000;
+
+    let mut group = c.benchmark_group("Day 11 Simulators");
+    let monkeys = day_11::parse(day_11::INPUT).unwrap();
+    let game = day_11::KeepAway::new(&monkeys, day_11::Relief::ModuloProduct).unwrap();
+
+    // both ways of playing are checked once up front, so that only playing is timed
+    let expected = game.play(NUM_ROUNDS).unwrap().num_inspections;
+    let round_robin = game
+        .play_observed(NUM_ROUNDS, &mut |_: &day_11::Throw| {})
+        .unwrap();
+    assert_eq!(round_robin.num_inspections, expected);
+
+    group.bench_function("Round Robin", |b| {
+        b.iter(|| {
+            game.play_observed(black_box(NUM_ROUNDS), &mut |_: &day_11::Throw| {})
+                .unwrap()
+        })
+    });
+
+    group.bench_function("Per Item", |b| {
+        b.iter(|| game.play(black_box(NUM_ROUNDS)).unwrap())
+    });
+
+    group.bench_function("Per Item 10^12 Rounds", |b| {
+        b.iter(|| game.play(black_box(1_000_000_000_000)).unwrap())
+    });
 }
 
-criterion_group!(day_11, benchmark_day_11);
+// the puzzle's monkeys, but with 100 items each rather than a handful, so there's enough work to
+// share between cores
+#[cfg(feature = "parallel")]
+fn benchmark_day_11_parallel(c: &mut Criterion) {
+    const NUM_ROUNDS: u64 = 10_000;
+    const ITEMS_PER_MONKEY: u64 = 100;
+
+    let mut group = c.benchmark_group("Day 11 Per Item");
+    let mut monkeys = day_11::parse(day_11::INPUT).unwrap();
+    for (idx, monkey) in (0..).zip(&mut monkeys) {
+        monkey.worries = (1..=ITEMS_PER_MONKEY).map(|i| i * 7_919 + idx).collect();
+    }
+
+    let serial = day_11::KeepAway::new(&monkeys, day_11::Relief::ModuloProduct).unwrap();
+    let mut parallel = day_11::KeepAway::new(&monkeys, day_11::Relief::ModuloProduct).unwrap();
+    parallel.set_parallel(true);
+    assert_eq!(
+        serial.play(NUM_ROUNDS).unwrap(),
+        parallel.play(NUM_ROUNDS).unwrap()
+    );
+
+    group.bench_function("Serial", |b| {
+        b.iter(|| serial.play(black_box(NUM_ROUNDS)).unwrap())
+    });
+
+    group.bench_function("Parallel", |b| {
+        b.iter(|| parallel.play(black_box(NUM_ROUNDS)).unwrap())
+    });
+}
+
+#[cfg(not(feature = "parallel"))]
+criterion_group!(day_11, benchmark_day_11, benchmark_day_11_simulators);
+#[cfg(feature = "parallel")]
+criterion_group!(
+    day_11,
+    benchmark_day_11,
+    benchmark_day_11_simulators,
+    benchmark_day_11_parallel
+);
 
 criterion_main!(
     day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11
@@ -0,0 +1,298 @@
+use std::fmt::Display;
+use std::io::Write;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+
+use aoc_2022::{
+    day_1, day_10, day_11, day_2, day_3, day_4, day_5, day_6, day_7, day_8, day_9, ocr,
+};
+
+const NUM_DAYS: u32 = 11;
+
+const USAGE: &str = "usage: aoc solve [<day>] [--input <file>]
+       aoc render 10 [--format text|blocks|half-blocks|pbm|png] [--scale <n>]
+                     [--input <file>] [--output <file>]";
+
+#[derive(Debug)]
+struct SolveArgs {
+    day: Option<u32>,
+    input: Option<String>,
+}
+
+fn parse_solve_args(mut args: impl Iterator<Item = String>) -> Result<SolveArgs> {
+    let mut result = SolveArgs {
+        day: None,
+        input: None,
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| anyhow!("expected a value after {arg}\n{USAGE}"))?;
+                result.input = Some(value);
+            }
+            _ if result.day.is_none() && !arg.starts_with("--") => {
+                let day = arg.parse().context("expected the day to be a number")?;
+                if !(1..=NUM_DAYS).contains(&day) {
+                    return Err(anyhow!(
+                        "expected a day from 1 to {NUM_DAYS}, but got {day}"
+                    ));
+                }
+                result.day = Some(day);
+            }
+            _ => return Err(anyhow!("unknown option {arg:?}\n{USAGE}")),
+        }
+    }
+
+    if result.input.is_some() && result.day.is_none() {
+        return Err(anyhow!("--input needs a day to solve\n{USAGE}"));
+    }
+
+    Ok(result)
+}
+
+fn default_input(day: u32) -> &'static str {
+    match day {
+        1 => day_1::INPUT,
+        2 => day_2::INPUT,
+        3 => day_3::INPUT,
+        4 => day_4::INPUT,
+        5 => day_5::INPUT,
+        6 => day_6::INPUT,
+        7 => day_7::INPUT,
+        8 => day_8::INPUT,
+        9 => day_9::INPUT,
+        10 => day_10::INPUT,
+        11 => day_11::INPUT,
+        _ => unreachable!("day {day} was checked when parsing the arguments"),
+    }
+}
+
+// parses the input once and solves both parts from the same model, timing every phase
+fn solve_day<M, A: Display, B: Display>(
+    day: u32,
+    input: &str,
+    parse: impl Fn(&str) -> Result<M>,
+    part_one: impl Fn(&M) -> Result<A>,
+    part_two: impl Fn(&M) -> Result<B>,
+) -> Result<()> {
+    let start = Instant::now();
+    let model = parse(input).with_context(|| format!("parsing day {day}"))?;
+    let parse_time = start.elapsed();
+
+    let start = Instant::now();
+    let answer_one = part_one(&model).with_context(|| format!("solving day {day} part one"))?;
+    let part_one_time = start.elapsed();
+
+    let start = Instant::now();
+    let answer_two = part_two(&model).with_context(|| format!("solving day {day} part two"))?;
+    let part_two_time = start.elapsed();
+
+    println!("Day {day} (parsed in {parse_time:.2?})");
+    println!("  Part One: {answer_one} ({part_one_time:.2?})");
+    println!("  Part Two: {answer_two} ({part_two_time:.2?})");
+
+    Ok(())
+}
+
+fn solve(args: SolveArgs) -> Result<()> {
+    let days = match args.day {
+        Some(day) => day..=day,
+        None => 1..=NUM_DAYS,
+    };
+
+    for day in days {
+        let input = match &args.input {
+            Some(path) => {
+                std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?
+            }
+            None => default_input(day).to_string(),
+        };
+
+        match day {
+            1 => solve_day(
+                day,
+                &input,
+                day_1::parse,
+                |elves| day_1::solve_part_one(elves),
+                |elves| day_1::solve_part_two(elves),
+            ),
+            2 => solve_day(
+                day,
+                &input,
+                day_2::parse,
+                |rounds| day_2::solve_part_one(rounds),
+                |rounds| day_2::solve_part_two(rounds),
+            ),
+            3 => solve_day(
+                day,
+                &input,
+                day_3::parse,
+                |rucksacks| day_3::solve_part_one(rucksacks),
+                |rucksacks| day_3::solve_part_two(rucksacks),
+            ),
+            4 => solve_day(
+                day,
+                &input,
+                day_4::parse,
+                |pairs| day_4::solve_part_one(pairs),
+                |pairs| day_4::solve_part_two(pairs),
+            ),
+            5 => solve_day(
+                day,
+                &input,
+                day_5::parse,
+                day_5::solve_part_one,
+                day_5::solve_part_two,
+            ),
+            6 => solve_day(
+                day,
+                &input,
+                day_6::parse,
+                |datastream| day_6::solve_part_one(datastream),
+                |datastream| day_6::solve_part_two(datastream),
+            ),
+            7 => solve_day(
+                day,
+                &input,
+                day_7::parse,
+                day_7::solve_part_one,
+                day_7::solve_part_two,
+            ),
+            8 => solve_day(
+                day,
+                &input,
+                day_8::parse,
+                day_8::solve_part_one,
+                day_8::solve_part_two,
+            ),
+            9 => solve_day(
+                day,
+                &input,
+                day_9::parse,
+                |moves| day_9::solve_part_one(moves),
+                |moves| day_9::solve_part_two(moves),
+            ),
+            // the screen only shows letters, so print those instead
+            10 => solve_day(
+                day,
+                &input,
+                day_10::parse,
+                |program| day_10::solve_part_one(program),
+                |program| ocr::recognize(&day_10::solve_part_two(program)?),
+            ),
+            11 => solve_day(
+                day,
+                &input,
+                day_11::parse,
+                |monkeys| day_11::solve_part_one(monkeys),
+                |monkeys| day_11::solve_part_two(monkeys),
+            ),
+            _ => unreachable!("day {day} was checked when parsing the arguments"),
+        }?;
+    }
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Format {
+    Text,
+    Blocks,
+    HalfBlocks,
+    Pbm,
+    Png,
+}
+
+#[derive(Debug)]
+struct RenderArgs {
+    day: u32,
+    format: Format,
+    scale: usize,
+    input: Option<String>,
+    output: Option<String>,
+}
+
+fn parse_render_args(mut args: impl Iterator<Item = String>) -> Result<RenderArgs> {
+    let day = args
+        .next()
+        .ok_or_else(|| anyhow!("expected a day to render\n{USAGE}"))?
+        .parse()
+        .context("expected the day to be a number")?;
+
+    let mut result = RenderArgs {
+        day,
+        format: Format::HalfBlocks,
+        scale: 10,
+        input: None,
+        output: None,
+    };
+
+    while let Some(flag) = args.next() {
+        let value = args
+            .next()
+            .ok_or_else(|| anyhow!("expected a value after {flag}\n{USAGE}"))?;
+
+        match flag.as_str() {
+            "--format" => {
+                result.format = match value.as_str() {
+                    "text" => Format::Text,
+                    "blocks" => Format::Blocks,
+                    "half-blocks" => Format::HalfBlocks,
+                    "pbm" => Format::Pbm,
+                    "png" => Format::Png,
+                    _ => return Err(anyhow!("unknown format {value:?}\n{USAGE}")),
+                }
+            }
+            "--scale" => {
+                result.scale = value.parse().context("expected the scale to be a number")?;
+                if result.scale < 1 {
+                    return Err(anyhow!("expected a scale of at least 1, but got {value}"));
+                }
+            }
+            "--input" => result.input = Some(value),
+            "--output" => result.output = Some(value),
+            _ => return Err(anyhow!("unknown option {flag:?}\n{USAGE}")),
+        }
+    }
+
+    Ok(result)
+}
+
+fn render(args: RenderArgs) -> Result<()> {
+    if args.day != 10 {
+        return Err(anyhow!("only day 10 can be rendered, not day {}", args.day));
+    }
+
+    let input = match &args.input {
+        Some(path) => std::fs::read_to_string(path).with_context(|| format!("reading {path}"))?,
+        None => day_10::INPUT.to_string(),
+    };
+    let screen = day_10::part_two(&input)?;
+
+    let bytes = match args.format {
+        Format::Text => day_10::render::ascii(&screen).into_bytes(),
+        Format::Blocks => day_10::render::blocks(&screen).into_bytes(),
+        Format::HalfBlocks => day_10::render::half_blocks(&screen).into_bytes(),
+        Format::Pbm => day_10::render::pbm(&screen).into_bytes(),
+        Format::Png => day_10::render::png(&screen, args.scale)?,
+    };
+
+    match &args.output {
+        Some(path) => std::fs::write(path, bytes).with_context(|| format!("writing {path}")),
+        None => Ok(std::io::stdout().write_all(&bytes)?),
+    }
+}
+
+fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+
+    match args.next().as_deref() {
+        Some("solve") => solve(parse_solve_args(args)?),
+        Some("render") => render(parse_render_args(args)?),
+        _ => Err(anyhow!("{USAGE}")),
+    }
+}
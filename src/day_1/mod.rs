@@ -4,7 +4,7 @@ use std::collections::BinaryHeap;
 
 use nom::{
     character::complete::{newline, u32},
-    combinator::{map, opt},
+    combinator::opt,
     multi::{count, separated_list1},
     sequence::terminated,
     IResult,
@@ -12,18 +12,19 @@ use nom::{
 
 pub const INPUT: &str = include_str!("./input");
 
-fn single_calorie_count(input: &str) -> IResult<&str, u32> {
-    map(separated_list1(newline, u32), |list| list.into_iter().sum())(input)
+fn single_elf_items(input: &str) -> IResult<&str, Vec<u32>> {
+    separated_list1(newline, u32)(input)
 }
 
-fn calories_list(input: &str) -> IResult<&str, Vec<u32>> {
+fn calories_list(input: &str) -> IResult<&str, Vec<Vec<u32>>> {
     terminated(
-        separated_list1(count(newline, 2), single_calorie_count),
+        separated_list1(count(newline, 2), single_elf_items),
         opt(newline),
     )(input)
 }
 
-fn parse_calories_list(input: &str) -> Result<Vec<u32>> {
+/// Parses the calories of every item each elf is carrying, one list per elf.
+pub fn parse(input: &str) -> Result<Vec<Vec<u32>>> {
     let (leftover, result) = calories_list(input).map_err(|err| err.map_input(str::to_string))?;
 
     if !leftover.is_empty() {
@@ -36,21 +37,20 @@ fn parse_calories_list(input: &str) -> Result<Vec<u32>> {
     Ok(result)
 }
 
-pub fn part_one(input: &str) -> Result<u32> {
-    let elf_calories = parse_calories_list(input)?;
+fn elf_calories(elves: &[Vec<u32>]) -> impl Iterator<Item = u32> + '_ {
+    elves.iter().map(|items| items.iter().sum())
+}
 
-    Ok(elf_calories
-        .into_iter()
+pub fn solve_part_one(elves: &[Vec<u32>]) -> Result<u32> {
+    elf_calories(elves)
         .max()
-        .expect("unexpected empty list of elf calories"))
+        .ok_or_else(|| anyhow!("unexpected empty list of elf calories"))
 }
 
-pub fn part_two(input: &str) -> Result<u32> {
+pub fn solve_part_two(elves: &[Vec<u32>]) -> Result<u32> {
     const NUM_ITEMS: usize = 3;
 
-    let elf_calories = parse_calories_list(input)?;
-
-    let mut heap = elf_calories.into_iter().collect::<BinaryHeap<_>>();
+    let mut heap = elf_calories(elves).collect::<BinaryHeap<_>>();
     let mut result = 0;
     for _ in 0..NUM_ITEMS {
         result += heap
@@ -61,9 +61,18 @@ pub fn part_two(input: &str) -> Result<u32> {
     Ok(result)
 }
 
+pub fn part_one(input: &str) -> Result<u32> {
+    solve_part_one(&parse(input)?)
+}
+
+pub fn part_two(input: &str) -> Result<u32> {
+    solve_part_two(&parse(input)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::assert_solves;
 
     const TEST_INPUT: &str = "1000
 2000
@@ -103,4 +112,17 @@ mod tests {
         let result = part_two(INPUT).unwrap();
         assert_eq!(result, 206_780);
     }
+
+    #[test]
+    fn test_model_without_text() {
+        let elves = vec![vec![1, 2], vec![7], vec![3, 3], vec![]];
+
+        assert_solves(
+            elves.as_slice(),
+            solve_part_one,
+            solve_part_two,
+            (7, 7 + 6 + 3),
+        );
+        assert_eq!(parse(TEST_INPUT).unwrap()[3], [7_000, 8_000, 9_000]);
+    }
 }
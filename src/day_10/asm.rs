@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{alpha1, alphanumeric1, char, i32, satisfy, space0, space1},
+    combinator::{all_consuming, map_res, opt, recognize, value},
+    multi::many0_count,
+    sequence::{pair, preceded, separated_pair, terminated},
+    IResult, Parser,
+};
+
+use crate::grid::Grid;
+
+use super::cpu::{Instruction, Register};
+
+#[derive(Clone, Copy, Debug)]
+enum Target<'a> {
+    Offset(i32),
+    Label(&'a str),
+}
+
+// an instruction whose jump target might still be a label
+#[derive(Clone, Copy, Debug)]
+enum Statement<'a> {
+    Resolved(Instruction),
+    Jump(Target<'a>),
+    JumpIfNotZero(Register, Target<'a>),
+}
+
+fn register(input: &str) -> IResult<&str, Register> {
+    map_res(satisfy(|c| c.is_ascii_lowercase()), Register::new)(input)
+}
+
+fn label(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        alt((alpha1, tag("_"))),
+        many0_count(alt((alphanumeric1, tag("_")))),
+    ))(input)
+}
+
+fn target(input: &str) -> IResult<&str, Target<'_>> {
+    alt((i32.map(Target::Offset), label.map(Target::Label)))(input)
+}
+
+fn statement(input: &str) -> IResult<&str, Statement<'_>> {
+    fn noop(input: &str) -> IResult<&str, Statement<'_>> {
+        value(Statement::Resolved(Instruction::Noop), tag("noop"))(input)
+    }
+
+    fn add(input: &str) -> IResult<&str, Statement<'_>> {
+        preceded(tag("add"), separated_pair(register, space1, i32))
+            .map(|(register, value)| Statement::Resolved(Instruction::Add(register, value)))
+            .parse(input)
+    }
+
+    fn jump(input: &str) -> IResult<&str, Statement<'_>> {
+        preceded(pair(tag("jmp"), space1), target)
+            .map(Statement::Jump)
+            .parse(input)
+    }
+
+    fn jump_if_not_zero(input: &str) -> IResult<&str, Statement<'_>> {
+        preceded(
+            pair(tag("jnz"), space1),
+            separated_pair(register, space1, target),
+        )
+        .map(|(register, target)| Statement::JumpIfNotZero(register, target))
+        .parse(input)
+    }
+
+    alt((noop, add, jump, jump_if_not_zero))(input)
+}
+
+// `[label:] [statement]`, with comments already stripped
+fn line(input: &str) -> IResult<&str, (Option<&str>, Option<Statement<'_>>)> {
+    all_consuming(terminated(
+        pair(
+            opt(terminated(label, pair(char(':'), space0))),
+            opt(statement),
+        ),
+        space0,
+    ))(input)
+}
+
+/// Assembles program text into instructions. Every line holds at most one instruction, optionally
+/// preceded by a `label:` that jumps can use in place of an offset. Anything after a `;` is a
+/// comment, and blank lines are ignored.
+pub fn assemble(source: &str) -> Result<Vec<Instruction>> {
+    let mut statements = Vec::new();
+    let mut labels = HashMap::new();
+
+    for (line_idx, text) in source.lines().enumerate() {
+        let code = text.split(';').next().unwrap_or_default().trim();
+
+        let (_, (label, statement)) = line(code).map_err(|_| {
+            anyhow!(
+                "line {}: expected an instruction or label, but got {:?}",
+                line_idx + 1,
+                text
+            )
+        })?;
+
+        if let Some(label) = label {
+            if labels.insert(label, statements.len()).is_some() {
+                return Err(anyhow!(
+                    "line {}: label `{}` is defined more than once",
+                    line_idx + 1,
+                    label
+                ));
+            }
+        }
+
+        if let Some(statement) = statement {
+            statements.push((line_idx + 1, statement));
+        }
+    }
+
+    let resolve = |line_number: usize, idx: usize, target: Target| -> Result<i32> {
+        match target {
+            Target::Offset(offset) => Ok(offset),
+            Target::Label(label) => {
+                let &label_idx = labels
+                    .get(label)
+                    .ok_or_else(|| anyhow!("line {}: unknown label `{}`", line_number, label))?;
+
+                Ok(i32::try_from(label_idx)? - i32::try_from(idx)?)
+            }
+        }
+    };
+
+    statements
+        .into_iter()
+        .enumerate()
+        .map(|(idx, (line_number, statement))| match statement {
+            Statement::Resolved(instruction) => Ok(instruction),
+            Statement::Jump(target) => Ok(Instruction::Jump(resolve(line_number, idx, target)?)),
+            Statement::JumpIfNotZero(register, target) => Ok(Instruction::JumpIfNotZero(
+                register,
+                resolve(line_number, idx, target)?,
+            )),
+        })
+        .collect()
+}
+
+/// Prints a program in canonical form, one instruction per line with jumps as plain offsets.
+pub fn disassemble(program: &[Instruction]) -> String {
+    program
+        .iter()
+        .map(|instruction| format!("{instruction}\n"))
+        .collect()
+}
+
+/// Compiles a program of `addx` and `noop` instructions that makes the CRT draw `target`, if there
+/// is one. `x` only changes at the end of an `addx`, so both of its cycles see the same sprite.
+pub fn compile_bitmap(target: &Grid<bool>) -> Result<Vec<Instruction>> {
+    // the sprite is three pixels wide, so these are far enough off screen to light nothing
+    let min_x = -2;
+    let max_x = i32::try_from(target.width())? + 1;
+    let num_x = usize::try_from(max_x - min_x + 1)?;
+
+    let num_cycles = target.width() * target.height();
+    let x_idx = |x: i32| (x - min_x) as usize;
+    let sprite_fits = |cycle: usize, x: i32| {
+        let position = (cycle % target.width(), cycle / target.width());
+        let covered = i64::abs_diff(position.0 as i64, i64::from(x)) <= 1;
+
+        covered == target[position]
+    };
+
+    // `previous[cycle][x]` is how the start of `cycle` was reached with `x` in the register: after
+    // a `noop` or after an `addx` from the given value, two cycles earlier
+    #[derive(Clone, Copy)]
+    enum Previous {
+        Unreachable,
+        Start,
+        Noop,
+        Addx(i32),
+    }
+
+    let mut previous = vec![vec![Previous::Unreachable; num_x]; num_cycles + 1];
+    previous[0][x_idx(1)] = Previous::Start;
+
+    for cycle in 0..num_cycles {
+        for x in min_x..=max_x {
+            if matches!(previous[cycle][x_idx(x)], Previous::Unreachable) || !sprite_fits(cycle, x)
+            {
+                continue;
+            }
+
+            if matches!(previous[cycle + 1][x_idx(x)], Previous::Unreachable) {
+                previous[cycle + 1][x_idx(x)] = Previous::Noop;
+            }
+
+            if cycle + 2 <= num_cycles && sprite_fits(cycle + 1, x) {
+                for next_x in min_x..=max_x {
+                    if matches!(previous[cycle + 2][x_idx(next_x)], Previous::Unreachable) {
+                        previous[cycle + 2][x_idx(next_x)] = Previous::Addx(x);
+                    }
+                }
+            }
+        }
+    }
+
+    let mut x = (min_x..=max_x)
+        .find(|&x| !matches!(previous[num_cycles][x_idx(x)], Previous::Unreachable))
+        .ok_or_else(|| anyhow!("no program of `addx` and `noop` instructions draws this bitmap"))?;
+
+    let mut program = Vec::new();
+    let mut cycle = num_cycles;
+    while cycle > 0 {
+        match previous[cycle][x_idx(x)] {
+            Previous::Noop => {
+                program.push(Instruction::Noop);
+                cycle -= 1;
+            }
+            Previous::Addx(from) => {
+                program.push(Instruction::Add(Register::X, x - from));
+                x = from;
+                cycle -= 2;
+            }
+            Previous::Start | Previous::Unreachable => {
+                unreachable!("cycle {} was reached without a previous instruction", cycle)
+            }
+        }
+    }
+    program.reverse();
+
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{cpu::Cpu, Crt, INPUT};
+    use super::*;
+    use crate::ocr;
+
+    #[test]
+    fn test_labels_and_comments() {
+        let source = "
+; count `a` down from 3
+    adda 3
+loop: addx 10   ; runs three times
+    adda -1
+    jnz a loop
+
+end:
+    jmp end2
+end2:
+";
+        let program = assemble(source).unwrap();
+        let a = Register::new('a').unwrap();
+
+        assert_eq!(
+            program,
+            [
+                Instruction::Add(a, 3),
+                Instruction::Add(Register::X, 10),
+                Instruction::Add(a, -1),
+                Instruction::JumpIfNotZero(a, -2),
+                Instruction::Jump(1),
+            ]
+        );
+        assert_eq!(
+            disassemble(&program),
+            "adda 3\naddx 10\nadda -1\njnz a -2\njmp 1\n"
+        );
+        assert_eq!(assemble(&disassemble(&program)).unwrap(), program);
+    }
+
+    #[test]
+    fn test_assembler_errors() {
+        assert!(assemble("jmp nowhere").is_err());
+        assert!(assemble("a: noop\na: noop").is_err());
+        assert!(assemble("noop noop").is_err());
+        assert!(assemble("mul x 2").is_err());
+    }
+
+    #[test]
+    fn test_compile_bitmap_round_trip() {
+        let program = assemble(INPUT).unwrap();
+        let mut cpu = Cpu::new(&program);
+        let mut crt = Crt::default();
+        cpu.run(&mut crt).unwrap();
+        let target = crt.into_screen().unwrap();
+
+        let program = compile_bitmap(&target).unwrap();
+        let text = disassemble(&program);
+        assert!(text
+            .lines()
+            .all(|line| line == "noop" || line.starts_with("addx ")));
+
+        let program = assemble(&text).unwrap();
+
+        let mut cpu = Cpu::new(&program);
+        let mut crt = Crt::default();
+        cpu.run(&mut crt).unwrap();
+
+        assert_eq!(cpu.cycles(), target.width() * target.height());
+        let screen = crt.into_screen().unwrap();
+        assert_eq!(screen, target);
+        assert_eq!(ocr::recognize(&screen).unwrap(), "BACEKLHF");
+    }
+
+    #[test]
+    fn test_impossible_bitmap() {
+        // `x` starts at 1, so the very first pixel is always lit
+        assert!(compile_bitmap(&Grid::new(40, 6, false)).is_err());
+    }
+}
@@ -0,0 +1,311 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+/// One of the device's 26 registers, named `a` through `z`. The puzzle only ever uses `x`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Register(u8);
+
+impl Register {
+    pub const X: Register = Register(b'x' - b'a');
+
+    pub fn new(name: char) -> Result<Self> {
+        if !name.is_ascii_lowercase() {
+            return Err(anyhow!(
+                "expected a register from `a` to `z`, but got {:?}",
+                name
+            ));
+        }
+
+        Ok(Self(name as u8 - b'a'))
+    }
+
+    pub fn name(self) -> char {
+        char::from(b'a' + self.0)
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Registers {
+    values: [i32; 26],
+}
+
+impl Registers {
+    pub fn get(&self, register: Register) -> i32 {
+        self.values[usize::from(register.0)]
+    }
+
+    pub fn x(&self) -> i32 {
+        self.get(Register::X)
+    }
+
+    fn get_mut(&mut self, register: Register) -> &mut i32 {
+        &mut self.values[usize::from(register.0)]
+    }
+}
+
+impl Default for Registers {
+    /// Every register starts at 0, except for `x` which starts at 1.
+    fn default() -> Self {
+        let mut result = Self { values: [0; 26] };
+        *result.get_mut(Register::X) = 1;
+
+        result
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// `noop`: does nothing for one cycle.
+    Noop,
+    /// `add<r> <value>`, e.g. `addx 3`: adds `value` to register `r` at the end of its second cycle.
+    Add(Register, i32),
+    /// `jmp <offset>`: continues at the instruction `offset` away from this one after one cycle.
+    Jump(i32),
+    /// `jnz <r> <offset>`: like `jmp`, but only when register `r` isn't zero.
+    JumpIfNotZero(Register, i32),
+}
+
+impl Instruction {
+    /// How many cycles the instruction takes to complete.
+    pub fn cycles(self) -> u32 {
+        match self {
+            Instruction::Noop | Instruction::Jump(_) | Instruction::JumpIfNotZero(..) => 1,
+            Instruction::Add(..) => 2,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Noop => write!(f, "noop"),
+            Instruction::Add(register, value) => write!(f, "add{register} {value}"),
+            Instruction::Jump(offset) => write!(f, "jmp {offset}"),
+            Instruction::JumpIfNotZero(register, offset) => write!(f, "jnz {register} {offset}"),
+        }
+    }
+}
+
+/// The state of the CPU *during* a clock cycle, before the instruction being executed completes.
+#[derive(Debug)]
+pub struct Cycle<'a> {
+    /// The 1-based number of this cycle.
+    pub number: usize,
+    pub registers: &'a Registers,
+}
+
+/// Gets to look at every cycle the CPU runs, e.g. to sample the signal strength or draw the CRT.
+pub trait Observer {
+    fn on_cycle(&mut self, cycle: &Cycle);
+}
+
+impl<F: FnMut(&Cycle)> Observer for F {
+    fn on_cycle(&mut self, cycle: &Cycle) {
+        self(cycle)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Cpu<'a> {
+    program: &'a [Instruction],
+    registers: Registers,
+    program_counter: usize,
+    // cycles already spent on the instruction at `program_counter`
+    elapsed: u32,
+    cycles: usize,
+    max_cycles: usize,
+}
+
+impl<'a> Cpu<'a> {
+    /// How many cycles a CPU runs before giving up, unless told otherwise. Far more than any
+    /// program without loops needs.
+    pub const DEFAULT_MAX_CYCLES: usize = 10_000_000;
+
+    pub fn new(program: &'a [Instruction]) -> Self {
+        Self {
+            program,
+            registers: Registers::default(),
+            program_counter: 0,
+            elapsed: 0,
+            cycles: 0,
+            max_cycles: Self::DEFAULT_MAX_CYCLES,
+        }
+    }
+
+    /// Makes [`Cpu::step`] fail instead of running more than `max_cycles` cycles in total, which
+    /// stops programs that loop forever.
+    pub fn set_max_cycles(&mut self, max_cycles: usize) {
+        self.max_cycles = max_cycles;
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    /// Index of the next instruction to execute.
+    pub fn program_counter(&self) -> usize {
+        self.program_counter
+    }
+
+    /// Number of cycles run so far.
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// Whether execution has run off the end of the program.
+    pub fn is_halted(&self) -> bool {
+        self.program_counter >= self.program.len()
+    }
+
+    /// Runs a single clock cycle, letting `observer` look at it before the current instruction
+    /// takes effect. Returns `false` without running anything once the CPU has halted, and fails
+    /// once it has used up its maximum number of cycles.
+    pub fn step(&mut self, observer: &mut impl Observer) -> Result<bool> {
+        let Some(&instruction) = self.program.get(self.program_counter) else {
+            return Ok(false);
+        };
+
+        if self.cycles >= self.max_cycles {
+            return Err(anyhow!(
+                "the program was still running at instruction {} after {} cycles",
+                self.program_counter,
+                self.max_cycles
+            ));
+        }
+
+        self.cycles += 1;
+        observer.on_cycle(&Cycle {
+            number: self.cycles,
+            registers: &self.registers,
+        });
+
+        self.elapsed += 1;
+        if self.elapsed < instruction.cycles() {
+            return Ok(true);
+        }
+
+        self.elapsed = 0;
+        self.program_counter = match instruction {
+            Instruction::Noop => self.program_counter + 1,
+            Instruction::Add(register, value) => {
+                let current = self.registers.get_mut(register);
+                *current = current.checked_add(value).ok_or_else(|| {
+                    anyhow!(
+                        "adding {} to register {} at instruction {} overflowed",
+                        value,
+                        register,
+                        self.program_counter
+                    )
+                })?;
+
+                self.program_counter + 1
+            }
+            Instruction::Jump(offset) => self.jump_target(offset)?,
+            Instruction::JumpIfNotZero(register, offset) => {
+                if self.registers.get(register) != 0 {
+                    self.jump_target(offset)?
+                } else {
+                    self.program_counter + 1
+                }
+            }
+        };
+
+        Ok(true)
+    }
+
+    fn jump_target(&self, offset: i32) -> Result<usize> {
+        isize::try_from(offset)
+            .ok()
+            .and_then(|offset| self.program_counter.checked_add_signed(offset))
+            .ok_or_else(|| {
+                anyhow!(
+                    "jump by {} at instruction {} lands before the start of the program",
+                    offset,
+                    self.program_counter
+                )
+            })
+    }
+
+    /// Runs until the program halts, or fails once it has run for the maximum number of cycles.
+    pub fn run(&mut self, observer: &mut impl Observer) -> Result<()> {
+        while self.step(observer)? {}
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cycle_timing() {
+        // the puzzle's small example
+        let mut cpu = Cpu::new(&[
+            Instruction::Noop,
+            Instruction::Add(Register::X, 3),
+            Instruction::Add(Register::X, -5),
+        ]);
+
+        let mut during = Vec::new();
+        cpu.run(&mut |cycle: &Cycle| during.push((cycle.number, cycle.registers.x())))
+            .unwrap();
+
+        assert_eq!(during, [(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]);
+        assert_eq!(cpu.registers().x(), -1);
+        assert_eq!(cpu.cycles(), 5);
+        assert!(cpu.is_halted());
+        assert!(!cpu.step(&mut |_: &Cycle| {}).unwrap());
+    }
+
+    #[test]
+    fn test_jumps() {
+        let a = Register::new('a').unwrap();
+
+        // count `a` down from 3, adding 10 to `x` each time round the loop
+        let program = [
+            Instruction::Add(a, 3),
+            Instruction::Add(Register::X, 10),
+            Instruction::Add(a, -1),
+            Instruction::JumpIfNotZero(a, -2),
+            Instruction::Noop,
+        ];
+        let mut cpu = Cpu::new(&program);
+        cpu.run(&mut |_: &Cycle| {}).unwrap();
+
+        assert_eq!(cpu.registers().x(), 31);
+        assert_eq!(cpu.registers().get(a), 0);
+        assert_eq!(cpu.cycles(), 2 + 3 * (2 + 2 + 1) + 1);
+
+        let mut cpu = Cpu::new(&[Instruction::Jump(-1)]);
+        assert!(cpu.step(&mut |_: &Cycle| {}).is_err());
+
+        assert!(Register::new('X').is_err());
+    }
+
+    #[test]
+    fn test_max_cycles() {
+        // `start: jmp start` never halts
+        let mut cpu = Cpu::new(&[Instruction::Noop, Instruction::Jump(0)]);
+        cpu.set_max_cycles(1_000);
+        assert!(cpu.run(&mut |_: &Cycle| {}).is_err());
+        assert_eq!(cpu.cycles(), 1_000);
+
+        assert!(Cpu::new(&[Instruction::Jump(0)])
+            .run(&mut |_: &Cycle| {})
+            .is_err());
+
+        // running exactly up to the limit is fine
+        let mut cpu = Cpu::new(&[Instruction::Noop, Instruction::Add(Register::X, 1)]);
+        cpu.set_max_cycles(3);
+        cpu.run(&mut |_: &Cycle| {}).unwrap();
+        assert!(cpu.is_halted());
+    }
+}
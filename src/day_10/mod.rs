@@ -1,122 +1,240 @@
 use anyhow::{anyhow, Result};
-use nom::{
-    branch::alt, bytes::complete::tag, character::complete::i8, character::complete::newline,
-    multi::separated_list1, sequence::preceded, IResult, Parser,
-};
+
+use crate::grid::Grid;
+
+pub mod asm;
+pub mod cpu;
+pub mod render;
+
+use cpu::{Cpu, Cycle, Instruction, Observer};
 
 pub const INPUT: &str = include_str!("./input");
 
-#[derive(Debug)]
-struct Nop;
-#[derive(Debug)]
-struct Addx(i8);
+pub const CRT_WIDTH: usize = 40;
+pub const CRT_HEIGHT: usize = 6;
 
-#[derive(Debug)]
-enum Instruction {
-    Nop(Nop),
-    Addx(Addx),
+/// What the CRT does with cycles after it has drawn its last pixel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Overflow {
+    /// Start over from the top left pixel, drawing over the previous frame.
+    Wrap,
+    /// Draw nothing.
+    Clip,
+    /// Fail once the screen is read.
+    Error,
 }
 
-fn instruction(input: &str) -> IResult<&str, Instruction> {
-    fn nop(input: &str) -> IResult<&str, Nop> {
-        tag("noop").map(|_| Nop).parse(input)
-    }
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrtConfig {
+    pub width: usize,
+    pub height: usize,
+    /// How many pixels the sprite reaches to either side of `x`, so a radius of 1 is 3 pixels wide.
+    pub sprite_radius: u32,
+    /// The first cycle the signal strength is sampled during.
+    pub first_sample: usize,
+    /// How many cycles apart later samples are.
+    pub sample_interval: usize,
+    pub overflow: Overflow,
+}
 
-    fn addx(input: &str) -> IResult<&str, Addx> {
-        preceded(tag("addx "), i8).map(Addx).parse(input)
+impl Default for CrtConfig {
+    /// The device from the puzzle, which errors instead of drawing past its 240 pixels.
+    fn default() -> Self {
+        Self {
+            width: CRT_WIDTH,
+            height: CRT_HEIGHT,
+            sprite_radius: 1,
+            first_sample: 20,
+            sample_interval: 40,
+            overflow: Overflow::Error,
+        }
     }
-
-    alt((nop.map(Instruction::Nop), addx.map(Instruction::Addx)))(input)
 }
 
-fn problem_statement(input: &str) -> IResult<&str, Vec<Instruction>> {
-    separated_list1(newline, instruction)(input)
+/// Sums up `cycle * x` during every cycle the config samples the signal at.
+#[derive(Debug)]
+pub struct SignalStrength {
+    first_sample: usize,
+    sample_interval: usize,
+    total: i64,
+    // the first sampled cycle whose signal strength didn't fit in the total
+    overflowed_at: Option<usize>,
 }
 
-fn parse_problem_statement(input: &str) -> Result<Vec<Instruction>> {
-    let (rest, problem_statement) =
-        problem_statement(input).map_err(|err| err.map_input(str::to_string))?;
+impl SignalStrength {
+    pub fn new(config: &CrtConfig) -> Result<Self> {
+        if config.sample_interval == 0 {
+            return Err(anyhow!(
+                "signal strength samples must be at least one cycle apart"
+            ));
+        }
 
-    if !rest.is_empty() {
-        return Err(anyhow!(
-            "expected full input stream to be parsed, but got {:?} left over",
-            rest
-        ));
+        Ok(Self {
+            first_sample: config.first_sample,
+            sample_interval: config.sample_interval,
+            total: 0,
+            overflowed_at: None,
+        })
     }
 
-    Ok(problem_statement)
+    /// The sum of the samples so far, or an error if it ever overflowed.
+    pub fn total(&self) -> Result<i64> {
+        if let Some(cycle) = self.overflowed_at {
+            return Err(anyhow!(
+                "the signal strength total overflowed during cycle {}",
+                cycle
+            ));
+        }
+
+        Ok(self.total)
+    }
 }
 
-pub fn part_one(input: &str) -> Result<i32> {
-    let problem_statement = parse_problem_statement(input)?;
-
-    let mut x = 1;
-    let mut cycle_values = Vec::new();
-    cycle_values.push(x);
-    for instruction in problem_statement {
-        match instruction {
-            Instruction::Addx(Addx(val)) => {
-                cycle_values.push(x);
-                cycle_values.push(x);
-                x += val;
+impl Default for SignalStrength {
+    fn default() -> Self {
+        // the default config has a non-zero interval
+        Self::new(&CrtConfig::default()).unwrap()
+    }
+}
+
+impl Observer for SignalStrength {
+    fn on_cycle(&mut self, cycle: &Cycle) {
+        if cycle.number >= self.first_sample
+            && (cycle.number - self.first_sample).is_multiple_of(self.sample_interval)
+        {
+            if self.overflowed_at.is_some() {
+                return;
             }
-            Instruction::Nop(_) => {
-                cycle_values.push(x);
+
+            let total = i64::try_from(cycle.number)
+                .ok()
+                .and_then(|number| number.checked_mul(i64::from(cycle.registers.x())))
+                .and_then(|strength| self.total.checked_add(strength));
+            match total {
+                Some(total) => self.total = total,
+                None => self.overflowed_at = Some(cycle.number),
             }
         }
     }
+}
+
+/// Lights the pixel being drawn during a cycle whenever the sprite centered on `x` covers it.
+#[derive(Debug)]
+pub struct Crt {
+    config: CrtConfig,
+    screen: Grid<bool>,
+    num_pixels: usize,
+    // the first cycle that went past the end of the screen, with `Overflow::Error`
+    overflowed_at: Option<usize>,
+}
+
+impl Crt {
+    pub fn new(config: CrtConfig) -> Result<Self> {
+        let num_pixels = match config.width.checked_mul(config.height) {
+            Some(0) => Err(anyhow!(
+                "a {}x{} screen has no pixels to draw",
+                config.width,
+                config.height
+            )),
+            Some(num_pixels) => Ok(num_pixels),
+            None => Err(anyhow!(
+                "a {}x{} screen has too many pixels",
+                config.width,
+                config.height
+            )),
+        }?;
+
+        Ok(Self {
+            screen: Grid::new(config.width, config.height, false),
+            config,
+            num_pixels,
+            overflowed_at: None,
+        })
+    }
 
-    let mut result = 0;
-    for (i, val) in cycle_values.into_iter().enumerate() {
-        if i >= 20 && ((i - 20) % 40) == 0 {
-            result += i32::try_from(i)? * i32::from(val);
-        }
+    /// The screen as drawn so far.
+    pub fn screen(&self) -> &Grid<bool> {
+        &self.screen
     }
 
-    Ok(result)
+    /// The finished screen, or an error if the program ran past its end with [`Overflow::Error`].
+    pub fn into_screen(self) -> Result<Grid<bool>> {
+        if let Some(cycle) = self.overflowed_at {
+            return Err(anyhow!(
+                "cycle {} ran past the end of the {}x{} screen",
+                cycle,
+                self.config.width,
+                self.config.height
+            ));
+        }
+
+        Ok(self.screen)
+    }
 }
 
-pub const CRT_WIDTH: usize = 40;
-pub const CRT_HEIGHT: usize = 6;
+impl Default for Crt {
+    fn default() -> Self {
+        // the default config has pixels
+        Self::new(CrtConfig::default()).unwrap()
+    }
+}
 
-pub fn part_two(input: &str) -> Result<[[bool; CRT_WIDTH]; CRT_HEIGHT]> {
-    let problem_statement = parse_problem_statement(input)?;
-    let mut crt = [[false; CRT_WIDTH]; CRT_HEIGHT];
-
-    let mut x = 1;
-    let mut cycle_values = Vec::new();
-    for instruction in problem_statement {
-        match instruction {
-            Instruction::Addx(Addx(val)) => {
-                cycle_values.push(x);
-                cycle_values.push(x);
-                x += val;
-            }
-            Instruction::Nop(_) => {
-                cycle_values.push(x);
+impl Observer for Crt {
+    fn on_cycle(&mut self, cycle: &Cycle) {
+        let mut pixel = cycle.number - 1;
+        if pixel >= self.num_pixels {
+            match self.config.overflow {
+                Overflow::Wrap => pixel %= self.num_pixels,
+                Overflow::Clip => return,
+                Overflow::Error => {
+                    self.overflowed_at.get_or_insert(cycle.number);
+                    return;
+                }
             }
         }
+
+        let position = (pixel % self.config.width, pixel / self.config.width);
+        let distance = i64::abs_diff(position.0 as i64, i64::from(cycle.registers.x()));
+        self.screen[position] = distance <= u64::from(self.config.sprite_radius);
     }
+}
 
-    for (i, val) in cycle_values.into_iter().enumerate() {
-        if val < 0 {
-            continue;
-        }
+/// The program as instructions, see [`asm::assemble`].
+pub fn parse(input: &str) -> Result<Vec<Instruction>> {
+    asm::assemble(input)
+}
 
-        let x_val = i % CRT_WIDTH;
-        let y_val = i / CRT_WIDTH;
+pub fn solve_part_one(program: &[Instruction]) -> Result<i64> {
+    let mut cpu = Cpu::new(program);
+    let mut signal_strength = SignalStrength::default();
 
-        if usize::abs_diff(x_val, usize::try_from(val)?) <= 1 {
-            crt[y_val][x_val] = true;
-        }
-    }
+    cpu.run(&mut signal_strength)?;
+
+    signal_strength.total()
+}
+
+pub fn solve_part_two(program: &[Instruction]) -> Result<Grid<bool>> {
+    let mut cpu = Cpu::new(program);
+    let mut crt = Crt::default();
+
+    cpu.run(&mut crt)?;
 
-    Ok(crt)
+    crt.into_screen()
+}
+
+pub fn part_one(input: &str) -> Result<i64> {
+    solve_part_one(&parse(input)?)
+}
+
+pub fn part_two(input: &str) -> Result<Grid<bool>> {
+    solve_part_two(&parse(input)?)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::cpu::{Instruction, Register, Registers};
     use super::*;
+    use crate::ocr;
 
     const TEST_INPUT: &str = "addx 15
 addx -11
@@ -271,6 +389,160 @@ noop";
         assert_eq!(result, 13_140);
     }
 
+    #[test]
+    fn test_both_observers() {
+        let program = asm::assemble(TEST_INPUT).unwrap();
+        let mut cpu = Cpu::new(&program);
+        let mut signal_strength = SignalStrength::default();
+        let mut crt = Crt::default();
+
+        cpu.run(&mut |cycle: &Cycle| {
+            signal_strength.on_cycle(cycle);
+            crt.on_cycle(cycle);
+        })
+        .unwrap();
+
+        assert_eq!(signal_strength.total().unwrap(), 13_140);
+        assert_eq!(crt.into_screen().unwrap(), part_two(TEST_INPUT).unwrap());
+    }
+
+    #[test]
+    fn test_crt_config() {
+        let draw = |source: &str, config: CrtConfig| {
+            let program = asm::assemble(source).unwrap();
+            let mut cpu = Cpu::new(&program);
+            let mut crt = Crt::new(config).unwrap();
+            cpu.run(&mut crt).unwrap();
+
+            crt.into_screen().map(|screen| {
+                screen
+                    .rows()
+                    .flat_map(|row| {
+                        row.iter()
+                            .map(|&lit| if lit { '#' } else { '.' })
+                            .chain(['\n'])
+                    })
+                    .collect::<String>()
+            })
+        };
+
+        let small = CrtConfig {
+            width: 5,
+            height: 2,
+            ..Default::default()
+        };
+        let noops = "noop\n".repeat(10);
+        assert_eq!(draw(&noops, small.clone()).unwrap(), "###..\n###..\n");
+        assert_eq!(
+            draw(
+                &noops,
+                CrtConfig {
+                    sprite_radius: 2,
+                    ..small.clone()
+                }
+            )
+            .unwrap(),
+            "####.\n####.\n"
+        );
+
+        // `x` jumps away after the first two cycles, and the last two cycles don't fit on screen
+        let program = "addx 9\nnoop\nnoop\nnoop";
+        let tiny = |overflow| CrtConfig {
+            width: 3,
+            height: 1,
+            overflow,
+            ..Default::default()
+        };
+        assert_eq!(draw(program, tiny(Overflow::Clip)).unwrap(), "##.\n");
+        assert_eq!(draw(program, tiny(Overflow::Wrap)).unwrap(), "...\n");
+        assert!(draw(program, tiny(Overflow::Error)).is_err());
+
+        assert!(Crt::new(CrtConfig {
+            width: 0,
+            ..Default::default()
+        })
+        .is_err());
+        assert!(Crt::new(CrtConfig {
+            width: usize::MAX,
+            height: 2,
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_sampling_schedule() {
+        let config = CrtConfig {
+            first_sample: 2,
+            sample_interval: 3,
+            ..Default::default()
+        };
+
+        // `x` is 1 during cycles 1 and 2, then 4 during 3 to 5
+        let program = asm::assemble("addx 3\nnoop\nnoop\nnoop").unwrap();
+        let mut cpu = Cpu::new(&program);
+        let mut signal_strength = SignalStrength::new(&config).unwrap();
+        cpu.run(&mut signal_strength).unwrap();
+        assert_eq!(signal_strength.total().unwrap(), 2 + 5 * 4);
+
+        assert!(SignalStrength::new(&CrtConfig {
+            sample_interval: 0,
+            ..Default::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_endless_loop() {
+        assert!(part_one("start: jmp start").is_err());
+        assert!(part_two("noop\nstart: addx 1\njmp start").is_err());
+    }
+
+    #[test]
+    fn test_signal_strength_overflow() {
+        // by the 20th cycle, `20 * x` is far beyond what an `i32` can hold
+        let program = format!("addx 2000000000\naddx 100000000\n{}", "noop\n".repeat(20));
+        assert_eq!(part_one(program.trim_end()).unwrap(), 20 * 2_100_000_001);
+
+        let mut signal_strength = SignalStrength::new(&CrtConfig {
+            first_sample: 1,
+            sample_interval: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        let registers = Registers::default();
+        for number in [1, usize::MAX, 2] {
+            signal_strength.on_cycle(&Cycle {
+                number,
+                registers: &registers,
+            });
+        }
+        assert!(signal_strength.total().is_err());
+    }
+
+    #[test]
+    fn test_extended_instructions() {
+        let program = asm::assemble("addy 2\njnz y 2\naddx 100\naddx -1").unwrap();
+        assert_eq!(program[0], Instruction::Add(Register::new('y').unwrap(), 2));
+
+        let mut cpu = Cpu::new(&program);
+        cpu.run(&mut |_: &Cycle| {}).unwrap();
+        assert_eq!(cpu.registers().x(), 0);
+
+        assert!(asm::assemble("addX 1").is_err());
+    }
+
+    #[test]
+    fn test_sprite_left_of_screen() {
+        // `x` is -1 from the third cycle on, where the sprite only covers column 0
+        let program = format!("addx -2\n{}", "noop\n".repeat(39));
+        let screen = part_two(program.trim_end()).unwrap();
+
+        assert!(screen[(0, 0)] && screen[(1, 0)] && !screen[(2, 0)]);
+        assert!(screen[(0, 1)]);
+        assert!(!screen[(1, 1)]);
+    }
+
     #[test]
     fn solution_part_one() {
         let result = part_one(INPUT).unwrap();
@@ -324,10 +596,9 @@ noop";
             ],
         ];
         let result = part_two(TEST_INPUT).unwrap();
-        assert_eq!(result, EXPECTED_RESULT);
+        assert_eq!(result, Grid::from(EXPECTED_RESULT));
     }
 
-    // BACEKLHF
     #[test]
     fn solution_part_two() {
         const EXPECTED_RESULT: [[bool; CRT_WIDTH]; CRT_HEIGHT] = [
@@ -338,7 +609,7 @@ noop";
                 true, false,
             ],
             [
-                false, false, false, true, false, true, false, false, true, false, true, false,
+                true, false, false, true, false, true, false, false, true, false, true, false,
                 false, true, false, true, false, false, false, false, true, false, true, false,
                 false, true, false, false, false, false, true, false, false, true, false, true,
                 false, false, false, false,
@@ -350,10 +621,10 @@ noop";
                 true, false, false,
             ],
             [
-                false, false, false, true, false, true, true, true, true, false, true, false,
-                false, false, false, true, false, false, false, false, true, false, true, false,
-                false, true, false, false, false, false, true, false, false, true, false, true,
-                false, false, false, false,
+                true, false, false, true, false, true, true, true, true, false, true, false, false,
+                false, false, true, false, false, false, false, true, false, true, false, false,
+                true, false, false, false, false, true, false, false, true, false, true, false,
+                false, false, false,
             ],
             [
                 true, false, false, true, false, true, false, false, true, false, true, false,
@@ -369,6 +640,7 @@ noop";
             ],
         ];
         let result = part_two(INPUT).unwrap();
-        assert_eq!(result, EXPECTED_RESULT);
+        assert_eq!(result, Grid::from(EXPECTED_RESULT));
+        assert_eq!(ocr::recognize(&result).unwrap(), "BACEKLHF");
     }
 }
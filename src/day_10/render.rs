@@ -0,0 +1,288 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+
+use crate::grid::Grid;
+
+use super::Crt;
+
+/// One line per row, with `#` for lit pixels and `.` for dark ones, as in the puzzle text.
+pub fn ascii(screen: &Grid<bool>) -> String {
+    let mut result = String::new();
+    for row in screen.rows() {
+        result.extend(row.iter().map(|&lit| if lit { '#' } else { '.' }));
+        result.push('\n');
+    }
+
+    result
+}
+
+/// One line per row, with a full block for every lit pixel.
+pub fn blocks(screen: &Grid<bool>) -> String {
+    let mut result = String::new();
+    for row in screen.rows() {
+        result.extend(row.iter().map(|&lit| if lit { '█' } else { ' ' }));
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Two rows of pixels per line using half blocks, which keeps pixels roughly square in a terminal.
+pub fn half_blocks(screen: &Grid<bool>) -> String {
+    let mut result = String::new();
+    for y in (0..screen.height()).step_by(2) {
+        result.extend((0..screen.width()).map(|x| {
+            let top = screen[(x, y)];
+            let bottom = screen.get((x, y + 1)).copied().unwrap_or(false);
+
+            match (top, bottom) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            }
+        }));
+        result.push('\n');
+    }
+
+    result
+}
+
+/// The screen as a plain (ASCII) PBM image, with lit pixels in black.
+pub fn pbm(screen: &Grid<bool>) -> String {
+    let mut result = format!("P1\n{} {}\n", screen.width(), screen.height());
+
+    for row in screen.rows() {
+        let line = row
+            .iter()
+            .map(|&lit| if lit { "1" } else { "0" })
+            .collect::<Vec<_>>()
+            .join(" ");
+        result.push_str(&line);
+        result.push('\n');
+    }
+
+    result
+}
+
+/// The screen as a grayscale PNG image with lit pixels in black, each drawn as a `scale` by
+/// `scale` square.
+pub fn png(screen: &Grid<bool>, scale: usize) -> Result<Vec<u8>> {
+    if scale == 0 {
+        return Err(anyhow!("expected a scale of at least 1, but got 0"));
+    }
+
+    let too_large = || anyhow!("a {scale}x scale makes the image too large for a PNG");
+    let width = screen.width().checked_mul(scale).ok_or_else(too_large)?;
+    let height = screen.height().checked_mul(scale).ok_or_else(too_large)?;
+    let png_width = u32::try_from(width).map_err(|_| too_large())?;
+    let png_height = u32::try_from(height).map_err(|_| too_large())?;
+
+    // every scanline starts with its filter type, which is always 0 (none), and all of them have
+    // to fit in a single IDAT chunk, which is checked before allocating any of them
+    let pixels_len = (width + 1).checked_mul(height).ok_or_else(too_large)?;
+    if zlib_stored_len(pixels_len) > u32::MAX as usize {
+        return Err(too_large());
+    }
+
+    let mut pixels = Vec::with_capacity(pixels_len);
+    for row in screen.rows() {
+        let mut scanline = vec![0];
+        for &lit in row {
+            let value = if lit { 0 } else { u8::MAX };
+            scanline.extend(std::iter::repeat_n(value, scale));
+        }
+
+        for _ in 0..scale {
+            pixels.extend_from_slice(&scanline);
+        }
+    }
+
+    let mut header = Vec::new();
+    header.extend(png_width.to_be_bytes());
+    header.extend(png_height.to_be_bytes());
+    // 8 bit grayscale, default compression and filtering, no interlacing
+    header.extend([8, 0, 0, 0, 0]);
+
+    let mut result = b"\x89PNG\r\n\x1a\n".to_vec();
+    push_chunk(&mut result, b"IHDR", &header)?;
+    push_chunk(&mut result, b"IDAT", &zlib_stored(&pixels))?;
+    push_chunk(&mut result, b"IEND", &[])?;
+
+    Ok(result)
+}
+
+fn push_chunk(result: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) -> Result<()> {
+    let len = u32::try_from(data.len()).map_err(|_| {
+        anyhow!(
+            "a PNG chunk holds at most {} bytes, but got {}",
+            u32::MAX,
+            data.len()
+        )
+    })?;
+    result.extend(len.to_be_bytes());
+
+    let start = result.len();
+    result.extend(kind);
+    result.extend(data);
+
+    let crc = crc32(&result[start..]);
+    result.extend(crc.to_be_bytes());
+
+    Ok(())
+}
+
+const MAXIMUM_BLOCK_LEN: usize = u16::MAX as usize;
+
+// the length of `zlib_stored(data)` for `len` bytes of data: a 2 byte header, a 5 byte header per
+// block (with at least one block) and a 4 byte checksum
+fn zlib_stored_len(len: usize) -> usize {
+    let num_blocks = len.div_ceil(MAXIMUM_BLOCK_LEN).max(1);
+    len.saturating_add(num_blocks * 5 + 6)
+}
+
+// a zlib stream of uncompressed deflate blocks, which is plenty for images this small
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut result = vec![0x78, 0x01];
+
+    let mut blocks = data.chunks(MAXIMUM_BLOCK_LEN).peekable();
+    if blocks.peek().is_none() {
+        // an empty stream still needs a final block
+        result.extend([1, 0, 0, 0xff, 0xff]);
+    }
+
+    while let Some(block) = blocks.next() {
+        let is_final = blocks.peek().is_none();
+        let len = block.len() as u16;
+
+        result.push(u8::from(is_final));
+        result.extend(len.to_le_bytes());
+        result.extend((!len).to_le_bytes());
+        result.extend(block);
+    }
+
+    result.extend(adler32(data).to_be_bytes());
+
+    result
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = u32::MAX;
+    for &byte in data {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MODULUS: u32 = 65_521;
+
+    let (mut a, mut b) = (1, 0);
+    for &byte in data {
+        a = (a + u32::from(byte)) % MODULUS;
+        b = (b + a) % MODULUS;
+    }
+
+    (b << 16) | a
+}
+
+/// Shows the screen drawn so far with [`ascii`], or with [`half_blocks`] for `{:#}`.
+impl fmt::Display for Crt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str(&half_blocks(self.screen()))
+        } else {
+            f.write_str(&ascii(self.screen()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{asm, cpu::Cpu, CrtConfig};
+    use super::*;
+
+    fn screen() -> Grid<bool> {
+        Grid::from([
+            [true, false, true],
+            [false, true, true],
+            [true, true, false],
+        ])
+    }
+
+    #[test]
+    fn test_text() {
+        let screen = screen();
+
+        assert_eq!(ascii(&screen), "#.#\n.##\n##.\n");
+        assert_eq!(blocks(&screen), "█ █\n ██\n██ \n");
+        assert_eq!(half_blocks(&screen), "▀▄█\n▀▀ \n");
+        assert_eq!(pbm(&screen), "P1\n3 3\n1 0 1\n0 1 1\n1 1 0\n");
+    }
+
+    #[test]
+    fn test_png() {
+        let png = png(&screen(), 2).unwrap();
+
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(&png[16..24], [0, 0, 0, 6, 0, 0, 0, 6]);
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+
+        // a single stored block of six 7 byte scanlines follows the zlib header
+        let idat = &png[33 + 8..];
+        assert_eq!(&idat[..7], [0x78, 0x01, 1, 42, 0, !42, 0xff]);
+        assert_eq!(&idat[7..14], [0, 0, 0, 255, 255, 0, 0]);
+        assert_eq!(&idat[14..21], [0, 0, 0, 255, 255, 0, 0]);
+        assert_eq!(&idat[21..28], [0, 255, 255, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_png_scale() {
+        assert!(png(&screen(), 0).is_err());
+        assert!(png(&screen(), usize::MAX).is_err());
+        assert!(png(&screen(), u32::MAX as usize).is_err());
+
+        // every side fits in a PNG, but the pixels don't fit in a chunk
+        assert!(png(&Grid::new(40, 6, true), 100_000).is_err());
+    }
+
+    #[test]
+    fn test_zlib_stored_len() {
+        for len in [
+            0,
+            1,
+            MAXIMUM_BLOCK_LEN,
+            MAXIMUM_BLOCK_LEN + 1,
+            3 * MAXIMUM_BLOCK_LEN,
+        ] {
+            assert_eq!(
+                zlib_stored_len(len),
+                zlib_stored(&vec![0; len]).len(),
+                "{len}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let program = asm::assemble("noop\nnoop\nnoop\nnoop").unwrap();
+        let mut cpu = Cpu::new(&program);
+        let mut crt = Crt::new(CrtConfig {
+            width: 4,
+            height: 1,
+            ..Default::default()
+        })
+        .unwrap();
+        cpu.run(&mut crt).unwrap();
+
+        assert_eq!(crt.to_string(), "###.\n");
+        assert_eq!(format!("{crt:#}"), "▀▀▀ \n");
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::{Monkey, Outcome};
+
+// where an item is at the start of a round, with its worry level already reduced
+type State = (usize, u64);
+
+/// Plays `num_rounds` rounds with worry levels kept modulo `supermodulo`, in time that doesn't
+/// depend on `num_rounds`. Every monkey's holdings are sorted, like they are for the other ways
+/// of playing.
+///
+/// Items never affect each other, and an item only has finitely many states at the start of a
+/// round, so every item's rounds eventually repeat. Each item is followed until it gets back to a
+/// state it was in before, and the rest of the rounds are made up by multiplying that cycle.
+pub(super) fn play(monkeys: &[Monkey], supermodulo: u64, num_rounds: u64) -> Result<Outcome> {
+    let trajectories = starting_states(monkeys, supermodulo)
+        .iter()
+        .map(|&item| item_inspections(monkeys, supermodulo, item, num_rounds))
+        .collect::<Result<_>>()?;
+
+    tally(monkeys.len(), trajectories)
+}
+
+/// Like [`play`], but follows the items on all cores at once.
+#[cfg(feature = "parallel")]
+pub(super) fn play_parallel(
+    monkeys: &[Monkey],
+    supermodulo: u64,
+    num_rounds: u64,
+) -> Result<Outcome> {
+    let trajectories = starting_states(monkeys, supermodulo)
+        .par_iter()
+        .map(|&item| item_inspections(monkeys, supermodulo, item, num_rounds))
+        .collect::<Result<_>>()?;
+
+    tally(monkeys.len(), trajectories)
+}
+
+fn starting_states(monkeys: &[Monkey], supermodulo: u64) -> Vec<State> {
+    monkeys
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, monkey)| {
+            monkey
+                .worries
+                .iter()
+                .map(move |&worry| (idx, worry % supermodulo))
+        })
+        .collect()
+}
+
+// adds up every item's inspections, and collects where the items ended up
+fn tally(num_monkeys: usize, trajectories: Vec<(Vec<u64>, State)>) -> Result<Outcome> {
+    let mut result = Outcome {
+        num_inspections: vec![0; num_monkeys],
+        holdings: vec![Vec::new(); num_monkeys],
+    };
+
+    for (counts, (holder, worry)) in trajectories {
+        result.holdings[holder].push(worry);
+
+        for (total, count) in result.num_inspections.iter_mut().zip(counts) {
+            *total = total
+                .checked_add(count)
+                .ok_or_else(|| anyhow!("too many inspections to count"))?;
+        }
+    }
+
+    for worries in &mut result.holdings {
+        worries.sort_unstable();
+    }
+
+    Ok(result)
+}
+
+// runs a single round for one item, adding its inspections to `counts`
+fn round(
+    monkeys: &[Monkey],
+    supermodulo: u64,
+    (mut idx, mut worry): State,
+    counts: &mut [u64],
+) -> Result<State> {
+    loop {
+        let monkey = &monkeys[idx];
+        worry = monkey.operation.evaluate_modulo(worry, supermodulo)?;
+        counts[idx] += 1;
+
+        let new_idx = if worry % monkey.test.divisible_by == 0 {
+            monkey.test.true_target
+        } else {
+            monkey.test.false_target
+        };
+
+        // monkeys later in the order still get to inspect the item this round, and no monkey
+        // throws to itself
+        if new_idx < idx {
+            return Ok((new_idx, worry));
+        }
+        idx = new_idx;
+    }
+}
+
+fn item_inspections(
+    monkeys: &[Monkey],
+    supermodulo: u64,
+    start: State,
+    num_rounds: u64,
+) -> Result<(Vec<u64>, State)> {
+    let num_monkeys = monkeys.len();
+
+    // the counts after every round so far, one row of `num_monkeys` per round starting at 0
+    let mut totals = vec![0; num_monkeys];
+    let mut states = vec![start];
+    let mut seen = HashMap::from([(start, 0)]);
+    let mut state = start;
+    let mut rounds_done = 0;
+
+    let cycle_start = loop {
+        if rounds_done == num_rounds {
+            // finished before the item got back to a state it was in before
+            return Ok((totals.split_off(totals.len() - num_monkeys), state));
+        }
+
+        let mut counts = totals[totals.len() - num_monkeys..].to_vec();
+        state = round(monkeys, supermodulo, state, &mut counts)?;
+        totals.extend(counts);
+        states.push(state);
+        rounds_done += 1;
+
+        if let Some(&previous) = seen.get(&state) {
+            break previous;
+        }
+        seen.insert(state, rounds_done);
+    };
+
+    let after = |round: u64| &totals[round as usize * num_monkeys..][..num_monkeys];
+
+    // the rounds from `cycle_start` to `rounds_done` repeat forever, and there are more to go
+    let cycle_len = rounds_done - cycle_start;
+    let num_cycles = (num_rounds - cycle_start) / cycle_len;
+    let remainder = (num_rounds - cycle_start) % cycle_len;
+
+    let counts = after(cycle_start + remainder)
+        .iter()
+        .zip(after(cycle_start).iter().zip(after(rounds_done)))
+        .map(|(&partial, (&cycle_from, &cycle_to))| {
+            (cycle_to - cycle_from)
+                .checked_mul(num_cycles)
+                .and_then(|cycles| cycles.checked_add(partial))
+                .ok_or_else(|| anyhow!("too many inspections to count"))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok((counts, states[(cycle_start + remainder) as usize]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{parse, tests::TEST_INPUT, INPUT};
+    use super::*;
+
+    // follows every item round by round, without looking for cycles
+    fn step_by_step(monkeys: &[Monkey], supermodulo: u64, num_rounds: u64) -> Outcome {
+        let mut result = Outcome {
+            num_inspections: vec![0; monkeys.len()],
+            holdings: vec![Vec::new(); monkeys.len()],
+        };
+
+        for (idx, monkey) in monkeys.iter().enumerate() {
+            for &worry in &monkey.worries {
+                let mut state = (idx, worry % supermodulo);
+                for _ in 0..num_rounds {
+                    state =
+                        round(monkeys, supermodulo, state, &mut result.num_inspections).unwrap();
+                }
+                result.holdings[state.0].push(state.1);
+            }
+        }
+
+        for worries in &mut result.holdings {
+            worries.sort_unstable();
+        }
+
+        result
+    }
+
+    #[test]
+    fn test_matches_step_by_step() {
+        for input in [TEST_INPUT, INPUT] {
+            let monkeys = parse(input).unwrap();
+            let supermodulo = monkeys.iter().map(|m| m.test.divisible_by).product();
+
+            for num_rounds in [0, 1, 20, 333, 10_000] {
+                let outcome = play(&monkeys, supermodulo, num_rounds).unwrap();
+                assert_eq!(outcome, step_by_step(&monkeys, supermodulo, num_rounds));
+                #[cfg(feature = "parallel")]
+                assert_eq!(
+                    play_parallel(&monkeys, supermodulo, num_rounds).unwrap(),
+                    outcome
+                );
+            }
+        }
+    }
+}
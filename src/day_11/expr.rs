@@ -0,0 +1,323 @@
+use std::fmt;
+
+use anyhow::{anyhow, Result};
+use nom::{
+    branch::alt,
+    bytes::complete::tag,
+    character::complete::{char, one_of, space0, u64},
+    combinator::all_consuming,
+    multi::many0,
+    sequence::{delimited, pair, terminated},
+    IResult, Parser,
+};
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> char {
+        match self {
+            BinaryOp::Add => '+',
+            BinaryOp::Sub => '-',
+            BinaryOp::Mul => '*',
+            BinaryOp::Div => '/',
+            BinaryOp::Rem => '%',
+        }
+    }
+}
+
+/// The right hand side of a monkey's `Operation: new = ...` line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    /// The worry level before the operation.
+    Old,
+    Int(u64),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// Evaluates the expression with plain integers, failing if the worry level would go negative,
+    /// overflow or be divided by zero.
+    pub fn evaluate(&self, old: u64) -> Result<u64> {
+        match self {
+            Expr::Old => Ok(old),
+            Expr::Int(val) => Ok(*val),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate(old)?, rhs.evaluate(old)?);
+                let result = match op {
+                    BinaryOp::Add => lhs.checked_add(rhs),
+                    BinaryOp::Sub => lhs.checked_sub(rhs),
+                    BinaryOp::Mul => lhs.checked_mul(rhs),
+                    BinaryOp::Div => lhs.checked_div(rhs),
+                    BinaryOp::Rem => lhs.checked_rem(rhs),
+                };
+
+                result.ok_or_else(|| evaluation_error(*op, lhs, rhs))
+            }
+        }
+    }
+
+    /// Like [`Expr::evaluate`], but worry levels can grow as large as they like.
+    #[cfg(feature = "bigint")]
+    pub fn evaluate_exact(&self, old: &BigUint) -> Result<BigUint> {
+        match self {
+            Expr::Old => Ok(old.clone()),
+            Expr::Int(val) => Ok(BigUint::from(*val)),
+            Expr::Binary(op, lhs, rhs) => {
+                let (lhs, rhs) = (lhs.evaluate_exact(old)?, rhs.evaluate_exact(old)?);
+                match op {
+                    BinaryOp::Sub if lhs < rhs => Err(evaluation_error(*op, lhs, rhs)),
+                    BinaryOp::Div | BinaryOp::Rem if rhs == BigUint::ZERO => {
+                        Err(evaluation_error(*op, lhs, rhs))
+                    }
+                    BinaryOp::Add => Ok(lhs + rhs),
+                    BinaryOp::Sub => Ok(lhs - rhs),
+                    BinaryOp::Mul => Ok(lhs * rhs),
+                    BinaryOp::Div => Ok(lhs / rhs),
+                    BinaryOp::Rem => Ok(lhs % rhs),
+                }
+            }
+        }
+    }
+
+    /// Checks that evaluating modulo `modulus` with [`Expr::evaluate_modulo`] gives the same result
+    /// as evaluating with plain integers and then reducing, which is what lets worry levels be kept
+    /// small. That holds for `+`, `-` and `*`, and for `% n` with a constant `n` dividing
+    /// `modulus`, but never for `/`.
+    ///
+    /// The two only agree where plain evaluation succeeds, though. A `-` that would make the worry
+    /// level negative fails with [`Expr::evaluate`], but wraps around modulo `modulus` here, since a
+    /// reduced worry level can't tell whether the real one was big enough.
+    pub fn check_ring_compatible(&self, modulus: u64) -> Result<()> {
+        match self {
+            Expr::Old | Expr::Int(_) => Ok(()),
+            Expr::Binary(BinaryOp::Rem, lhs, rhs) => match **rhs {
+                Expr::Int(divisor) if divisor != 0 && modulus.is_multiple_of(divisor) => {
+                    lhs.check_ring_compatible(modulus)
+                }
+                _ => Err(anyhow!(
+                    "`%` only keeps worry levels modulo {} intact with a constant dividing it",
+                    modulus
+                )),
+            },
+            Expr::Binary(BinaryOp::Div, ..) => Err(anyhow!(
+                "`/` can't be evaluated on worry levels kept modulo {}",
+                modulus
+            )),
+            Expr::Binary(_, lhs, rhs) => {
+                lhs.check_ring_compatible(modulus)?;
+                rhs.check_ring_compatible(modulus)
+            }
+        }
+    }
+
+    /// Evaluates the expression on integers modulo `modulus`, so the result is always below it.
+    /// Only meaningful once [`Expr::check_ring_compatible`] has passed.
+    pub fn evaluate_modulo(&self, old: u64, modulus: u64) -> Result<u64> {
+        let modulus_wide = u128::from(modulus);
+
+        match self {
+            Expr::Old => Ok(old % modulus),
+            Expr::Int(val) => Ok(val % modulus),
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = u128::from(lhs.evaluate_modulo(old, modulus)?);
+
+                let result = match op {
+                    BinaryOp::Add => lhs + u128::from(rhs.evaluate_modulo(old, modulus)?),
+                    BinaryOp::Sub => {
+                        lhs + modulus_wide - u128::from(rhs.evaluate_modulo(old, modulus)?)
+                    }
+                    BinaryOp::Mul => lhs * u128::from(rhs.evaluate_modulo(old, modulus)?),
+                    // a constant dividing the modulus, which the ring check guarantees
+                    BinaryOp::Rem => match **rhs {
+                        Expr::Int(divisor) if divisor != 0 => lhs % u128::from(divisor),
+                        _ => return Err(anyhow!("`%` needs a constant divisor here")),
+                    },
+                    BinaryOp::Div => {
+                        return Err(anyhow!(
+                            "`/` can't be evaluated on worry levels kept modulo {}",
+                            modulus
+                        ))
+                    }
+                };
+
+                // the result is below the modulus, so it fits back into a `u64`
+                Ok((result % modulus_wide) as u64)
+            }
+        }
+    }
+}
+
+fn evaluation_error(op: BinaryOp, lhs: impl fmt::Display, rhs: impl fmt::Display) -> anyhow::Error {
+    match op {
+        BinaryOp::Add | BinaryOp::Mul => anyhow!(
+            "{} {} {} overflows a 64 bit worry level",
+            lhs,
+            op.symbol(),
+            rhs
+        ),
+        BinaryOp::Sub => anyhow!("{} - {} would make the worry level negative", lhs, rhs),
+        BinaryOp::Div | BinaryOp::Rem => anyhow!("{} {} 0 divides by zero", lhs, op.symbol()),
+    }
+}
+
+fn binary_op(input: &str) -> IResult<&str, BinaryOp> {
+    one_of("+-*/%")
+        .map(|symbol| match symbol {
+            '+' => BinaryOp::Add,
+            '-' => BinaryOp::Sub,
+            '*' => BinaryOp::Mul,
+            '/' => BinaryOp::Div,
+            _ => BinaryOp::Rem,
+        })
+        .parse(input)
+}
+
+fn factor(input: &str) -> IResult<&str, Expr> {
+    alt((
+        tag("old").map(|_| Expr::Old),
+        u64.map(Expr::Int),
+        delimited(
+            terminated(char('('), space0),
+            expression,
+            terminated(space0, char(')')),
+        ),
+    ))(input)
+}
+
+// folds `first (op operand)*` to the left, keeping only operators that `accepts`
+fn left_associative<'a>(
+    input: &'a str,
+    operand: fn(&'a str) -> IResult<&'a str, Expr>,
+    accepts: fn(BinaryOp) -> bool,
+) -> IResult<&'a str, Expr> {
+    let operator = |input: &'a str| {
+        let (rest, op) = delimited(space0, binary_op, space0)(input)?;
+        if accepts(op) {
+            Ok((rest, op))
+        } else {
+            Err(nom::Err::Error(nom::error::Error::new(
+                input,
+                nom::error::ErrorKind::OneOf,
+            )))
+        }
+    };
+
+    let (rest, first) = operand(input)?;
+    let (rest, others) = many0(pair(operator, operand))(rest)?;
+
+    let result = others.into_iter().fold(first, |lhs, (op, rhs)| {
+        Expr::Binary(op, Box::new(lhs), Box::new(rhs))
+    });
+
+    Ok((rest, result))
+}
+
+fn term(input: &str) -> IResult<&str, Expr> {
+    left_associative(input, factor, |op| {
+        matches!(op, BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem)
+    })
+}
+
+/// Parses an expression over `old` and non-negative integers with `+`, `-`, `*`, `/`, `%` and
+/// parentheses, using the usual precedence.
+pub fn expression(input: &str) -> IResult<&str, Expr> {
+    left_associative(input, term, |op| {
+        matches!(op, BinaryOp::Add | BinaryOp::Sub)
+    })
+}
+
+pub fn parse(input: &str) -> Result<Expr> {
+    let (_, result) = all_consuming(delimited(space0, expression, space0))(input)
+        .map_err(|err| err.map_input(str::to_string))?;
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_precedence() {
+        let expr = parse("old * old + 3 * (old - 1) % 5").unwrap();
+
+        // 7 * 7 + ((3 * 6) % 5)
+        assert_eq!(expr.evaluate(7).unwrap(), 49 + 3);
+        assert_eq!(parse("10 - 4 - 3").unwrap().evaluate(0).unwrap(), 3);
+        assert_eq!(parse("( old/2 )*2").unwrap().evaluate(9).unwrap(), 8);
+        assert_eq!(parse("old + old + old").unwrap().evaluate(5).unwrap(), 15);
+
+        assert!(parse("old +").is_err());
+        assert!(parse("(old").is_err());
+        assert!(parse("new").is_err());
+    }
+
+    #[test]
+    fn test_evaluation_errors() {
+        assert!(parse("old - 5").unwrap().evaluate(3).is_err());
+        assert!(parse("old / (old - old)").unwrap().evaluate(3).is_err());
+        assert!(parse("old * old").unwrap().evaluate(u64::MAX).is_err());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_exact_evaluation() {
+        let expr = parse("old * old - (old - 1) / 2").unwrap();
+        let old = BigUint::from(u64::MAX);
+
+        let expected = BigUint::from(u64::MAX) * u64::MAX - (u64::MAX - 1) / 2;
+        assert_eq!(expr.evaluate_exact(&old).unwrap(), expected);
+        assert_eq!(
+            expr.evaluate_exact(&BigUint::from(7u64)).unwrap(),
+            BigUint::from(expr.evaluate(7).unwrap())
+        );
+
+        assert!(parse("old - 5").unwrap().evaluate_exact(&old).is_ok());
+        assert!(parse("5 - old").unwrap().evaluate_exact(&old).is_err());
+        assert!(parse("old % 0").unwrap().evaluate_exact(&old).is_err());
+    }
+
+    #[test]
+    fn test_ring_compatibility() {
+        const MODULUS: u64 = 2 * 3 * 5 * 7;
+
+        let compatible = parse("(old - 7) * old + old % 14 + 1000").unwrap();
+        compatible.check_ring_compatible(MODULUS).unwrap();
+        for old in 7..500 {
+            let expected = compatible.evaluate(old).unwrap() % MODULUS;
+            assert_eq!(compatible.evaluate_modulo(old, MODULUS).unwrap(), expected);
+            assert_eq!(
+                compatible.evaluate_modulo(old % MODULUS, MODULUS).unwrap(),
+                expected
+            );
+        }
+
+        // going negative is an error with plain integers, but wraps around modulo the modulus
+        let negative = parse("old - 5").unwrap();
+        negative.check_ring_compatible(MODULUS).unwrap();
+        assert!(negative.evaluate(3).is_err());
+        assert_eq!(negative.evaluate_modulo(3, MODULUS).unwrap(), MODULUS - 2);
+
+        assert!(parse("old / 3")
+            .unwrap()
+            .check_ring_compatible(MODULUS)
+            .is_err());
+        assert!(parse("old % 11")
+            .unwrap()
+            .check_ring_compatible(MODULUS)
+            .is_err());
+        assert!(parse("old % old")
+            .unwrap()
+            .check_ring_compatible(MODULUS)
+            .is_err());
+    }
+}
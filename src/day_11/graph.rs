@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+
+use super::{Observer, Throw};
+
+/// Counts how often every monkey throws to every other one, as an [`Observer`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ThrowGraph {
+    num_monkeys: usize,
+    edges: BTreeMap<(usize, usize), u64>,
+}
+
+impl ThrowGraph {
+    /// A graph without any throws yet, which still shows all `num_monkeys` monkeys.
+    pub fn new(num_monkeys: usize) -> Self {
+        Self {
+            num_monkeys,
+            edges: BTreeMap::new(),
+        }
+    }
+
+    /// How many items `from` has thrown to `to`.
+    pub fn num_throws(&self, from: usize, to: usize) -> u64 {
+        self.edges.get(&(from, to)).copied().unwrap_or(0)
+    }
+
+    /// Every pair of monkeys with at least one throw between them, with the number of throws.
+    pub fn edges(&self) -> impl Iterator<Item = (usize, usize, u64)> + '_ {
+        self.edges
+            .iter()
+            .map(|(&(from, to), &count)| (from, to, count))
+    }
+
+    /// The graph in Graphviz's DOT language, with every edge labelled by its number of throws.
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph keep_away {\n");
+
+        let num_monkeys = self
+            .edges
+            .keys()
+            .map(|&(from, to)| from.max(to) + 1)
+            .fold(self.num_monkeys, usize::max);
+        for idx in 0..num_monkeys {
+            result.push_str(&format!("    {idx} [label=\"Monkey {idx}\"];\n"));
+        }
+
+        for (from, to, count) in self.edges() {
+            result.push_str(&format!("    {from} -> {to} [label=\"{count}\"];\n"));
+        }
+
+        result.push_str("}\n");
+
+        result
+    }
+}
+
+impl<W> Observer<W> for ThrowGraph {
+    fn on_throw(&mut self, throw: &Throw<W>) {
+        *self.edges.entry((throw.monkey, throw.target)).or_default() += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{parse, tests::TEST_INPUT, KeepAway, Relief};
+    use super::*;
+
+    #[test]
+    fn test_throw_graph() {
+        let monkeys = parse(TEST_INPUT).unwrap();
+        let game = KeepAway::new(&monkeys, Relief::DivideBy(3)).unwrap();
+        let mut graph = ThrowGraph::new(game.num_monkeys());
+        let outcome = game.play_observed(1, &mut graph).unwrap();
+
+        // monkey 2 threw one of its three items to monkey 1, and the rest to monkey 3
+        assert_eq!(graph.num_throws(2, 1), 1);
+        assert_eq!(graph.num_throws(2, 3), 2);
+        assert_eq!(graph.num_throws(3, 2), 0);
+        for (idx, &count) in outcome.num_inspections.iter().enumerate() {
+            let thrown: u64 = graph
+                .edges()
+                .filter(|&(from, ..)| from == idx)
+                .map(|(.., count)| count)
+                .sum();
+            assert_eq!(thrown, count);
+        }
+
+        assert_eq!(
+            graph.to_dot(),
+            "digraph keep_away {
+    0 [label=\"Monkey 0\"];
+    1 [label=\"Monkey 1\"];
+    2 [label=\"Monkey 2\"];
+    3 [label=\"Monkey 3\"];
+    0 -> 3 [label=\"2\"];
+    1 -> 0 [label=\"4\"];
+    2 -> 1 [label=\"1\"];
+    2 -> 3 [label=\"2\"];
+    3 -> 1 [label=\"5\"];
+}
+"
+        );
+    }
+}
@@ -2,96 +2,48 @@ use std::collections::BinaryHeap;
 
 use anyhow::{anyhow, Result};
 use nom::{
-    branch::alt,
     bytes::complete::tag,
-    character::complete::{char, newline},
+    character::complete::newline,
     character::complete::{u16, u64},
     multi::{count, separated_list1},
     sequence::{delimited, preceded, terminated, tuple},
     IResult, Parser,
 };
+#[cfg(feature = "bigint")]
+use num_bigint::BigUint;
 
-pub const INPUT: &str = include_str!("./input");
+mod cycles;
+pub mod expr;
+pub mod graph;
 
-#[derive(Debug)]
-struct Monkey {
-    worries: Vec<u64>,
-    operation: Operation,
-    test: Test,
-}
-
-#[derive(Debug)]
-enum OperationModifier {
-    Add,
-    Mul,
-}
-
-#[derive(Debug)]
-enum OperationTarget {
-    Worry,
-    Int(u64),
-}
+use expr::Expr;
 
-#[derive(Debug)]
-struct Operation {
-    modifier: OperationModifier,
-    target: OperationTarget,
-}
-
-impl Operation {
-    fn evaluate(&self, old: u64) -> u64 {
-        let other = match self.target {
-            OperationTarget::Worry => old,
-            OperationTarget::Int(val) => val,
-        };
+pub const INPUT: &str = include_str!("./input");
 
-        match self.modifier {
-            OperationModifier::Add => old + other,
-            OperationModifier::Mul => old * other,
-        }
-    }
+/// One monkey's notes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Monkey {
+    /// The worry levels of the items the monkey starts out holding, in the order it inspects them.
+    pub worries: Vec<u64>,
+    pub operation: Expr,
+    pub test: Test,
 }
 
-#[derive(Debug)]
-struct Test {
-    divisible_by: u64,
-    true_target: usize,
-    false_target: usize,
+/// Where a monkey throws an item, depending on whether its worry level is divisible by
+/// `divisible_by`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Test {
+    pub divisible_by: u64,
+    pub true_target: usize,
+    pub false_target: usize,
 }
 
 fn starting_items(input: &str) -> IResult<&str, Vec<u64>> {
     preceded(tag("  Starting items: "), separated_list1(tag(", "), u64))(input)
 }
 
-fn operation(input: &str) -> IResult<&str, Operation> {
-    fn operation_target_worry(input: &str) -> IResult<&str, OperationTarget> {
-        tag("old").map(|_| OperationTarget::Worry).parse(input)
-    }
-
-    fn operation_target_int(input: &str) -> IResult<&str, OperationTarget> {
-        u64.map(OperationTarget::Int).parse(input)
-    }
-
-    fn operation_modifier_add(input: &str) -> IResult<&str, OperationModifier> {
-        char('+').map(|_| OperationModifier::Add).parse(input)
-    }
-
-    fn operation_modifier_mul(input: &str) -> IResult<&str, OperationModifier> {
-        char('*').map(|_| OperationModifier::Mul).parse(input)
-    }
-
-    let rest = input;
-    let (rest, _) = tag("  Operation: new = old ")(rest)?;
-    let (rest, operation_modifier) = alt((operation_modifier_add, operation_modifier_mul))(rest)?;
-    let (rest, _) = char(' ')(rest)?;
-    let (rest, operation_target) = alt((operation_target_int, operation_target_worry))(rest)?;
-
-    let result = Operation {
-        modifier: operation_modifier,
-        target: operation_target,
-    };
-
-    Ok((rest, result))
+fn operation(input: &str) -> IResult<&str, Expr> {
+    preceded(tag("  Operation: new = "), expr::expression)(input)
 }
 
 fn test(input: &str) -> IResult<&str, Test> {
@@ -133,7 +85,9 @@ fn problem_statement(input: &str) -> IResult<&str, Vec<Monkey>> {
     separated_list1(count(newline, 2), monkey)(input)
 }
 
-fn parse_problem_statement(input: &str) -> Result<Vec<Monkey>> {
+/// Reads the monkeys' notes, in the order the monkeys take their turns. Targets aren't checked
+/// until the monkeys start a [`KeepAway`] game.
+pub fn parse(input: &str) -> Result<Vec<Monkey>> {
     let (rest, problem_statement) =
         problem_statement(input).map_err(|err| err.map_input(str::to_string))?;
 
@@ -147,84 +101,301 @@ fn parse_problem_statement(input: &str) -> Result<Vec<Monkey>> {
     Ok(problem_statement)
 }
 
-pub fn part_one(input: &str) -> Result<u64> {
-    const NUM_ROUNDS: usize = 20;
-
-    let mut monkeys = parse_problem_statement(input)?;
-    let mut num_inspections: Vec<u64> = monkeys.iter().map(|_| 0).collect();
-
-    for _ in 0..NUM_ROUNDS {
-        for idx in 0..monkeys.len() {
-            while let Some(inspected) = monkeys[idx].worries.pop() {
-                // relief that item wasn't broken causes worry to be divided by 3 after inspection
-                let new_worry = monkeys[idx].operation.evaluate(inspected) / 3;
-                let new_idx = if new_worry % monkeys[idx].test.divisible_by == 0 {
-                    monkeys[idx].test.true_target
-                } else {
-                    monkeys[idx].test.false_target
-                };
-
-                monkeys[new_idx].worries.push(new_worry);
-                num_inspections[idx] += 1;
-            }
+/// What happens to an item's worry level after a monkey inspects it, once it's clear the item
+/// wasn't damaged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relief {
+    /// Worry levels are divided by the given amount, rounding down. The puzzle's part one uses 3.
+    DivideBy(u64),
+    /// Nothing happens, but worry levels are only kept modulo the product of the monkeys'
+    /// divisors, which is all their tests can tell apart. Every operation has to keep working on
+    /// reduced worry levels, see [`Expr::check_ring_compatible`]. Unlike with the other kinds of
+    /// relief, a `-` that makes a worry level negative wraps around instead of failing.
+    ModuloProduct,
+    /// Nothing happens, and worry levels are kept exactly. They soon stop fitting in a `u64`, so
+    /// this needs `play_exact` from the `bigint` feature for more than a handful of rounds.
+    None,
+}
+
+/// How a game of keep away ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome<W = u64> {
+    /// How many items every monkey inspected.
+    pub num_inspections: Vec<u64>,
+    /// The worry levels of the items every monkey holds at the end, in ascending order. Skipping
+    /// ahead loses the order items were thrown in, so every way of playing sorts them.
+    pub holdings: Vec<Vec<W>>,
+}
+
+impl<W> Outcome<W> {
+    /// The product of the two highest inspection counts.
+    pub fn monkey_business(&self) -> Result<u128> {
+        let mut inspections_heap = BinaryHeap::from(self.num_inspections.clone());
+        if inspections_heap.len() < 2 {
+            return Err(anyhow!(
+                "expected to have at least 2 monkeys with valid inspection values, but got {:?}",
+                inspections_heap
+            ));
         }
+
+        // popping twice is safe because we have verified that the heap has at least two elements.
+        let (first, second) = (
+            inspections_heap.pop().unwrap(),
+            inspections_heap.pop().unwrap(),
+        );
+
+        Ok(u128::from(first) * u128::from(second))
     }
+}
 
-    let mut inspections_heap = BinaryHeap::from(num_inspections);
-    if inspections_heap.len() < 2 {
-        return Err(anyhow!(
-            "expected to have at least 2 monkeys with valid inspection values, but got {:?}",
-            inspections_heap
-        ));
+/// One monkey throwing one item, after inspecting it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Throw<W = u64> {
+    /// The 1-based number of the round the throw happens in.
+    pub round: u64,
+    pub monkey: usize,
+    /// The item's worry level before the monkey inspected it.
+    pub old_worry: W,
+    /// The item's worry level when it's thrown, after any relief.
+    pub new_worry: W,
+    /// The monkey the item is thrown to.
+    pub target: usize,
+}
+
+/// Gets to look at every throw in a game, e.g. to log them or draw a [`graph::ThrowGraph`].
+pub trait Observer<W = u64> {
+    fn on_throw(&mut self, throw: &Throw<W>);
+}
+
+impl<W, F: FnMut(&Throw<W>)> Observer<W> for F {
+    fn on_throw(&mut self, throw: &Throw<W>) {
+        self(throw)
     }
+}
 
-    // popping twice is safe because we have verified that the heap has at least two elements.
-    Ok(inspections_heap.pop().unwrap() * inspections_heap.pop().unwrap())
+// a worry level that can be played with one round after the other
+trait WorryLevel {
+    fn is_divisible_by(&self, divisor: u64) -> bool;
 }
 
-pub fn part_two(input: &str) -> Result<u64> {
-    const NUM_ROUNDS: usize = 10_000;
-
-    let mut monkeys = parse_problem_statement(input)?;
-    let mut num_inspections: Vec<u64> = monkeys.iter().map(|_| 0).collect();
-
-    //
-    let supermodulo: u64 = monkeys.iter().map(|m| m.test.divisible_by).product();
-
-    for _ in 0..NUM_ROUNDS {
-        for idx in 0..monkeys.len() {
-            while let Some(inspected) = monkeys[idx].worries.pop() {
-                // Worry level is no longer divided by 3 after inspection.
-                let new_worry = monkeys[idx].operation.evaluate(inspected % supermodulo);
-                let new_idx = if new_worry % monkeys[idx].test.divisible_by == 0 {
-                    monkeys[idx].test.true_target
-                } else {
-                    monkeys[idx].test.false_target
-                };
-
-                monkeys[new_idx].worries.push(new_worry);
-                num_inspections[idx] += 1;
+impl WorryLevel for u64 {
+    fn is_divisible_by(&self, divisor: u64) -> bool {
+        self.is_multiple_of(divisor)
+    }
+}
+
+#[cfg(feature = "bigint")]
+impl WorryLevel for BigUint {
+    fn is_divisible_by(&self, divisor: u64) -> bool {
+        self % divisor == BigUint::ZERO
+    }
+}
+
+/// The monkeys' game of keep away, which can be played for any number of rounds.
+#[derive(Debug)]
+pub struct KeepAway<'a> {
+    monkeys: &'a [Monkey],
+    relief: Relief,
+    // only needed for `Relief::ModuloProduct`
+    supermodulo: Option<u64>,
+    #[cfg(feature = "parallel")]
+    parallel: bool,
+}
+
+impl<'a> KeepAway<'a> {
+    /// Sets up a game between `monkeys`, checking that every monkey throws to another one that
+    /// exists and that their notes work with `relief`.
+    pub fn new(monkeys: &'a [Monkey], relief: Relief) -> Result<Self> {
+        for (idx, monkey) in monkeys.iter().enumerate() {
+            if monkey.test.divisible_by == 0 {
+                return Err(anyhow!("monkey {} tests for divisibility by 0", idx));
+            }
+
+            for target in [monkey.test.true_target, monkey.test.false_target] {
+                if target >= monkeys.len() {
+                    return Err(anyhow!(
+                        "monkey {} throws to monkey {}, but there are only {} monkeys",
+                        idx,
+                        target,
+                        monkeys.len()
+                    ));
+                }
+
+                // the monkey would keep inspecting the same item forever
+                if target == idx {
+                    return Err(anyhow!("monkey {} throws items to itself", idx));
+                }
             }
         }
+
+        let supermodulo = match relief {
+            Relief::DivideBy(0) => return Err(anyhow!("can't divide worry levels by 0")),
+            Relief::ModuloProduct => Some(supermodulo(monkeys)?),
+            Relief::DivideBy(_) | Relief::None => None,
+        };
+
+        Ok(Self {
+            monkeys,
+            relief,
+            supermodulo,
+            #[cfg(feature = "parallel")]
+            parallel: false,
+        })
     }
 
-    let mut inspections_heap = BinaryHeap::from(num_inspections);
-    if inspections_heap.len() < 2 {
-        return Err(anyhow!(
-            "expected to have at least 2 monkeys with valid inspection values, but got {:?}",
-            inspections_heap
-        ));
+    /// Whether [`KeepAway::play`] follows items on all cores at once when it skips ahead. It
+    /// doesn't unless told to, since the puzzle's handful of items is over too quickly to share.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel(&mut self, parallel: bool) {
+        self.parallel = parallel;
+    }
+
+    pub fn num_monkeys(&self) -> usize {
+        self.monkeys.len()
     }
 
-    // popping twice is safe because we have verified that the heap has at least two elements.
-    Ok(inspections_heap.pop().unwrap() * inspections_heap.pop().unwrap())
+    /// Plays `num_rounds` rounds. With [`Relief::ModuloProduct`] this takes about as long for any
+    /// number of rounds, otherwise every round is played out one after the other.
+    pub fn play(&self, num_rounds: u64) -> Result<Outcome> {
+        match self.supermodulo {
+            #[cfg(feature = "parallel")]
+            Some(supermodulo) if self.parallel => {
+                cycles::play_parallel(self.monkeys, supermodulo, num_rounds)
+            }
+            Some(supermodulo) => cycles::play(self.monkeys, supermodulo, num_rounds),
+            None => self.play_observed(num_rounds, &mut |_: &Throw| {}),
+        }
+    }
+
+    /// Plays `num_rounds` rounds one after the other, letting `observer` look at every throw.
+    pub fn play_observed(&self, num_rounds: u64, observer: &mut impl Observer) -> Result<Outcome> {
+        let inspect = |old: &u64, operation: &Expr| match (self.relief, self.supermodulo) {
+            (Relief::DivideBy(divisor), _) => Ok(operation.evaluate(*old)? / divisor),
+            (Relief::ModuloProduct, Some(supermodulo)) => {
+                operation.evaluate_modulo(*old, supermodulo)
+            }
+            _ => operation.evaluate(*old),
+        };
+
+        let holdings = self.monkeys.iter().map(|m| m.worries.clone()).collect();
+        self.play_out(holdings, inspect, num_rounds, observer)
+    }
+
+    /// Plays `num_rounds` rounds one after the other with worry levels that never overflow, which
+    /// is slow but makes a good check on the other ways of playing. Worry levels are only ever
+    /// divided by [`Relief::DivideBy`], and never reduced.
+    #[cfg(feature = "bigint")]
+    pub fn play_exact(&self, num_rounds: u64) -> Result<Outcome<BigUint>> {
+        let divisor = match self.relief {
+            Relief::DivideBy(divisor) => divisor,
+            Relief::ModuloProduct | Relief::None => 1,
+        };
+        let inspect =
+            |old: &BigUint, operation: &Expr| Ok(operation.evaluate_exact(old)? / divisor);
+
+        let holdings = self
+            .monkeys
+            .iter()
+            .map(|m| m.worries.iter().copied().map(BigUint::from).collect())
+            .collect();
+        self.play_out(holdings, inspect, num_rounds, &mut |_: &Throw<BigUint>| {})
+    }
+
+    fn play_out<W: WorryLevel + Ord>(
+        &self,
+        mut holdings: Vec<Vec<W>>,
+        inspect: impl Fn(&W, &Expr) -> Result<W>,
+        num_rounds: u64,
+        observer: &mut impl Observer<W>,
+    ) -> Result<Outcome<W>> {
+        let mut num_inspections = vec![0u64; self.monkeys.len()];
+
+        for round in 1..=num_rounds {
+            for (idx, monkey) in self.monkeys.iter().enumerate() {
+                for inspected in std::mem::take(&mut holdings[idx]) {
+                    let new_worry = inspect(&inspected, &monkey.operation)?;
+                    let new_idx = if new_worry.is_divisible_by(monkey.test.divisible_by) {
+                        monkey.test.true_target
+                    } else {
+                        monkey.test.false_target
+                    };
+
+                    let throw = Throw {
+                        round,
+                        monkey: idx,
+                        old_worry: inspected,
+                        new_worry,
+                        target: new_idx,
+                    };
+                    observer.on_throw(&throw);
+
+                    holdings[new_idx].push(throw.new_worry);
+                    num_inspections[idx] += 1;
+                }
+            }
+        }
+
+        for worries in &mut holdings {
+            worries.sort_unstable();
+        }
+
+        Ok(Outcome {
+            num_inspections,
+            holdings,
+        })
+    }
+}
+
+// every test only cares about the worry level modulo its divisor, so as long as operations are
+// compatible with that, worry levels can be kept modulo the product of all divisors
+fn supermodulo(monkeys: &[Monkey]) -> Result<u64> {
+    let supermodulo = monkeys
+        .iter()
+        .try_fold(1u64, |acc, m| acc.checked_mul(m.test.divisible_by))
+        .ok_or_else(|| anyhow!("the product of all the monkeys' divisors overflowed"))?;
+
+    for (idx, monkey) in monkeys.iter().enumerate() {
+        monkey
+            .operation
+            .check_ring_compatible(supermodulo)
+            .map_err(|err| anyhow!("monkey {}'s operation can't be used here: {}", idx, err))?;
+    }
+
+    Ok(supermodulo)
+}
+
+pub fn solve_part_one(monkeys: &[Monkey]) -> Result<u64> {
+    const NUM_ROUNDS: u64 = 20;
+
+    // relief that item wasn't broken causes worry to be divided by 3 after inspection
+    let outcome = KeepAway::new(monkeys, Relief::DivideBy(3))?.play(NUM_ROUNDS)?;
+
+    Ok(u64::try_from(outcome.monkey_business()?)?)
+}
+
+pub fn solve_part_two(monkeys: &[Monkey]) -> Result<u64> {
+    const NUM_ROUNDS: u64 = 10_000;
+
+    // worry level is no longer divided by 3 after inspection
+    let outcome = KeepAway::new(monkeys, Relief::ModuloProduct)?.play(NUM_ROUNDS)?;
+
+    Ok(u64::try_from(outcome.monkey_business()?)?)
+}
+
+pub fn part_one(input: &str) -> Result<u64> {
+    solve_part_one(&parse(input)?)
+}
+
+pub fn part_two(input: &str) -> Result<u64> {
+    solve_part_two(&parse(input)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::assert_solves;
 
-    const TEST_INPUT: &str = "Monkey 0:
+    pub(super) const TEST_INPUT: &str = "Monkey 0:
   Starting items: 79, 98
   Operation: new = old * 19
   Test: divisible by 23
@@ -275,4 +446,200 @@ Monkey 3:
         let result = part_two(INPUT).unwrap();
         assert_eq!(result, 18_170_818_354);
     }
+
+    #[test]
+    fn test_custom_operations() {
+        let input = TEST_INPUT
+            .replace("new = old * 19", "new = (old - 1) * 19 + 19")
+            .replace("new = old + 3", "new = old + old % 17 * 0 + 3");
+        assert_eq!(part_one(&input).unwrap(), 10_605);
+        assert_eq!(part_two(&input).unwrap(), 2_713_310_158);
+
+        let input = TEST_INPUT.replace("new = old + 3", "new = old / 2 + 3");
+        assert!(part_one(&input).is_ok());
+        assert!(part_two(&input).is_err());
+
+        // going negative fails when playing with exact worry levels, but wraps around when they're
+        // kept modulo the product of the divisors
+        let input = TEST_INPUT.replace("new = old + 3", "new = old - 100");
+        let monkeys = parse(&input).unwrap();
+        let exact = KeepAway::new(&monkeys, Relief::None).unwrap();
+        let reduced = KeepAway::new(&monkeys, Relief::ModuloProduct).unwrap();
+        assert!(exact.play(1).is_err());
+        assert!(reduced.play(1).is_ok());
+        assert!(reduced.play_observed(1, &mut |_: &Throw| {}).is_ok());
+    }
+
+    #[test]
+    fn test_many_rounds() {
+        let monkeys = parse(TEST_INPUT).unwrap();
+        let game = KeepAway::new(&monkeys, Relief::ModuloProduct).unwrap();
+
+        // the puzzle's own checkpoints
+        assert_eq!(game.play(1).unwrap().num_inspections, [2, 4, 3, 6]);
+        assert_eq!(game.play(20).unwrap().num_inspections, [99, 97, 8, 103]);
+        assert_eq!(
+            game.play(1_000).unwrap().num_inspections,
+            [5_204, 4_792, 199, 5_192]
+        );
+        assert_eq!(game.play(0).unwrap().monkey_business().unwrap(), 0);
+
+        // skipping ahead ends up with the same items in the same places as playing every round
+        let monkeys = parse(INPUT).unwrap();
+        let game = KeepAway::new(&monkeys, Relief::ModuloProduct).unwrap();
+        for num_rounds in [1, 20, 1_000] {
+            assert_eq!(
+                game.play(num_rounds).unwrap(),
+                game.play_observed(num_rounds, &mut |_: &Throw| {}).unwrap()
+            );
+        }
+
+        let result = game
+            .play(1_000_000_000_000)
+            .unwrap()
+            .monkey_business()
+            .unwrap();
+        assert!(result > u128::from(u64::MAX));
+    }
+
+    #[test]
+    fn test_relief() {
+        // the puzzle's state after the first round of part one
+        let monkeys = parse(TEST_INPUT).unwrap();
+        let outcome = KeepAway::new(&monkeys, Relief::DivideBy(3))
+            .unwrap()
+            .play(1)
+            .unwrap();
+        assert_eq!(outcome.num_inspections, [2, 4, 3, 5]);
+        assert_eq!(
+            outcome.holdings,
+            [
+                vec![20, 23, 26, 27],
+                vec![25, 167, 207, 401, 1046, 2080],
+                vec![],
+                vec![]
+            ]
+        );
+
+        // without any relief, worry levels are exact until they overflow
+        let game = KeepAway::new(&monkeys, Relief::None).unwrap();
+        let supermodulo = 23 * 19 * 13 * 17;
+        let exact = game.play(3).unwrap();
+        let reduced = KeepAway::new(&monkeys, Relief::ModuloProduct)
+            .unwrap()
+            .play(3)
+            .unwrap();
+        assert_eq!(exact.num_inspections, reduced.num_inspections);
+        for (exact, reduced) in exact.holdings.iter().zip(&reduced.holdings) {
+            let mut exact: Vec<_> = exact.iter().map(|worry| worry % supermodulo).collect();
+            exact.sort_unstable();
+            assert_eq!(&exact, reduced);
+        }
+        assert!(game.play(20).is_err());
+
+        assert!(KeepAway::new(&monkeys, Relief::DivideBy(0)).is_err());
+    }
+
+    #[test]
+    fn test_throw_log() {
+        let monkeys = parse(TEST_INPUT).unwrap();
+        let game = KeepAway::new(&monkeys, Relief::ModuloProduct).unwrap();
+        let mut log = Vec::new();
+        let outcome = game
+            .play_observed(20, &mut |throw: &Throw| log.push(throw.clone()))
+            .unwrap();
+
+        // the round by round game agrees with the one that skips ahead
+        assert_eq!(outcome, game.play(20).unwrap());
+        assert_eq!(
+            log.len() as u64,
+            outcome.num_inspections.iter().sum::<u64>()
+        );
+        assert_eq!(
+            log[0],
+            Throw {
+                round: 1,
+                monkey: 0,
+                old_worry: 79,
+                new_worry: 79 * 19,
+                target: 3
+            }
+        );
+        assert!(log.iter().all(|throw| throw.new_worry < 23 * 19 * 13 * 17));
+        assert_eq!(log.last().unwrap().round, 20);
+    }
+
+    #[test]
+    fn test_invalid_targets() {
+        let to_itself =
+            TEST_INPUT.replace("If false: throw to monkey 1", "If false: throw to monkey 3");
+        let missing =
+            TEST_INPUT.replace("If true: throw to monkey 0", "If true: throw to monkey 4");
+        let divisible_by_zero = TEST_INPUT.replace("divisible by 17", "divisible by 0");
+
+        for input in [to_itself, missing, divisible_by_zero] {
+            let monkeys = parse(&input).unwrap();
+            assert!(KeepAway::new(&monkeys, Relief::DivideBy(3)).is_err());
+            assert!(KeepAway::new(&monkeys, Relief::ModuloProduct).is_err());
+            assert!(part_one(&input).is_err());
+        }
+    }
+
+    #[test]
+    fn test_model_without_text() {
+        let monkey =
+            |worries: Vec<u64>, operation: &str, divisible_by, true_target, false_target| Monkey {
+                worries,
+                operation: expr::parse(operation).unwrap(),
+                test: Test {
+                    divisible_by,
+                    true_target,
+                    false_target,
+                },
+            };
+        let monkeys = [
+            monkey(vec![79, 98], "old * 19", 23, 2, 3),
+            monkey(vec![54, 65, 75, 74], "old + 6", 19, 2, 0),
+            monkey(vec![79, 60, 97], "old * old", 13, 1, 3),
+            monkey(vec![74], "old + 3", 17, 0, 1),
+        ];
+
+        assert_eq!(parse(TEST_INPUT).unwrap(), monkeys);
+        assert_solves(
+            monkeys.as_slice(),
+            solve_part_one,
+            solve_part_two,
+            (10_605, 2_713_310_158),
+        );
+
+        let mut to_itself = monkeys.to_vec();
+        to_itself[3].test.false_target = 3;
+        assert!(KeepAway::new(&to_itself, Relief::DivideBy(3)).is_err());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_exact_worry_levels() {
+        let monkeys = parse(TEST_INPUT).unwrap();
+        let game = KeepAway::new(&monkeys, Relief::DivideBy(3)).unwrap();
+        let exact = game.play_exact(20).unwrap();
+        let outcome = game.play(20).unwrap();
+        assert_eq!(exact.num_inspections, outcome.num_inspections);
+        for (exact, worries) in exact.holdings.iter().zip(&outcome.holdings) {
+            let worries: Vec<_> = worries.iter().copied().map(BigUint::from).collect();
+            assert_eq!(exact, &worries);
+        }
+
+        // without relief, worry levels outgrow a `u64` within 20 rounds, but the counts match the
+        // puzzle's, which it worked out modulo the product of the divisors
+        let game = KeepAway::new(&monkeys, Relief::None).unwrap();
+        assert!(game.play(20).is_err());
+        let exact = game.play_exact(20).unwrap();
+        assert_eq!(exact.num_inspections, [99, 97, 8, 103]);
+        assert!(exact
+            .holdings
+            .iter()
+            .flatten()
+            .any(|worry| worry.bits() > 64));
+    }
 }
@@ -12,8 +12,8 @@ use nom::{
 
 pub const INPUT: &str = include_str!("./input");
 
-#[derive(Clone, Copy, Debug)]
-enum Choice {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Choice {
     Rock,
     Paper,
     Scissors,
@@ -25,30 +25,65 @@ struct GameRound {
     opponent_choice: Choice,
 }
 
-struct RoundPartOne(GameRound);
+/// The second column of the strategy guide, which means something different in each part.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Column {
+    X,
+    Y,
+    Z,
+}
 
-#[derive(Clone, Copy, Debug)]
-struct RoundPartTwo {
-    opponent_choice: Choice,
-    needed_result: GameRoundResult,
-}
-
-impl RoundPartTwo {
-    fn calculate_my_choice(&self) -> Choice {
-        match (self.opponent_choice, self.needed_result) {
-            (Choice::Rock, GameRoundResult::Winner(Player::Opponent)) => Choice::Scissors,
-            (Choice::Paper, GameRoundResult::Winner(Player::Opponent)) => Choice::Rock,
-            (Choice::Scissors, GameRoundResult::Winner(Player::Opponent)) => Choice::Paper,
-            (Choice::Rock, GameRoundResult::Tie) => Choice::Rock,
-            (Choice::Paper, GameRoundResult::Tie) => Choice::Paper,
-            (Choice::Scissors, GameRoundResult::Tie) => Choice::Scissors,
-            (Choice::Rock, GameRoundResult::Winner(Player::Me)) => Choice::Paper,
-            (Choice::Paper, GameRoundResult::Winner(Player::Me)) => Choice::Scissors,
-            (Choice::Scissors, GameRoundResult::Winner(Player::Me)) => Choice::Rock,
+/// One line of the strategy guide.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Round {
+    pub opponent_choice: Choice,
+    pub column: Column,
+}
+
+impl Round {
+    // the second column is what I should play
+    fn as_choice(&self) -> GameRound {
+        let my_choice = match self.column {
+            Column::X => Choice::Rock,
+            Column::Y => Choice::Paper,
+            Column::Z => Choice::Scissors,
+        };
+
+        GameRound {
+            my_choice,
+            opponent_choice: self.opponent_choice,
+        }
+    }
+
+    // the second column is how the round needs to end
+    fn as_needed_result(&self) -> GameRound {
+        let needed_result = match self.column {
+            Column::X => GameRoundResult::Winner(Player::Opponent),
+            Column::Y => GameRoundResult::Tie,
+            Column::Z => GameRoundResult::Winner(Player::Me),
+        };
+
+        GameRound {
+            my_choice: calculate_my_choice(self.opponent_choice, needed_result),
+            opponent_choice: self.opponent_choice,
         }
     }
 }
 
+fn calculate_my_choice(opponent_choice: Choice, needed_result: GameRoundResult) -> Choice {
+    match (opponent_choice, needed_result) {
+        (Choice::Rock, GameRoundResult::Winner(Player::Opponent)) => Choice::Scissors,
+        (Choice::Paper, GameRoundResult::Winner(Player::Opponent)) => Choice::Rock,
+        (Choice::Scissors, GameRoundResult::Winner(Player::Opponent)) => Choice::Paper,
+        (Choice::Rock, GameRoundResult::Tie) => Choice::Rock,
+        (Choice::Paper, GameRoundResult::Tie) => Choice::Paper,
+        (Choice::Scissors, GameRoundResult::Tie) => Choice::Scissors,
+        (Choice::Rock, GameRoundResult::Winner(Player::Me)) => Choice::Paper,
+        (Choice::Paper, GameRoundResult::Winner(Player::Me)) => Choice::Scissors,
+        (Choice::Scissors, GameRoundResult::Winner(Player::Me)) => Choice::Rock,
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 enum Player {
     Me,
@@ -103,10 +138,10 @@ impl GameRound {
     }
 }
 
-fn line_part_one(input: &str) -> IResult<&str, RoundPartOne> {
+fn line(input: &str) -> IResult<&str, Round> {
     map(
         separated_pair(one_of("ABC"), char(' '), one_of("XYZ")),
-        |(opponent_char, my_char)| {
+        |(opponent_char, column_char)| {
             let opponent_choice = match opponent_char {
                 'A' => Choice::Rock,
                 'B' => Choice::Paper,
@@ -114,26 +149,27 @@ fn line_part_one(input: &str) -> IResult<&str, RoundPartOne> {
                 other => unreachable!("unexpected opponent choice {other}"),
             };
 
-            let my_choice = match my_char {
-                'X' => Choice::Rock,
-                'Y' => Choice::Paper,
-                'Z' => Choice::Scissors,
-                other => unreachable!("unexpected self choice {other}"),
+            let column = match column_char {
+                'X' => Column::X,
+                'Y' => Column::Y,
+                'Z' => Column::Z,
+                other => unreachable!("unexpected second column {other}"),
             };
 
-            RoundPartOne(GameRound {
-                my_choice,
+            Round {
                 opponent_choice,
-            })
+                column,
+            }
         },
     )(input)
 }
 
-fn game_part_one(input: &str) -> IResult<&str, Vec<RoundPartOne>> {
-    terminated(separated_list1(newline, line_part_one), opt(newline))(input)
+fn game(input: &str) -> IResult<&str, Vec<Round>> {
+    terminated(separated_list1(newline, line), opt(newline))(input)
 }
-fn parse_game_part_one(input: &str) -> Result<Vec<RoundPartOne>> {
-    let (leftover, result) = game_part_one(input).map_err(|err| err.map_input(str::to_string))?;
+
+pub fn parse(input: &str) -> Result<Vec<Round>> {
+    let (leftover, result) = game(input).map_err(|err| err.map_input(str::to_string))?;
 
     if !leftover.is_empty() {
         return Err(anyhow!(
@@ -145,75 +181,32 @@ fn parse_game_part_one(input: &str) -> Result<Vec<RoundPartOne>> {
     Ok(result)
 }
 
-fn line_part_two(input: &str) -> IResult<&str, RoundPartTwo> {
-    map(
-        separated_pair(one_of("ABC"), char(' '), one_of("XYZ")),
-        |(opponent_char, my_char)| {
-            let opponent_choice = match opponent_char {
-                'A' => Choice::Rock,
-                'B' => Choice::Paper,
-                'C' => Choice::Scissors,
-                other => unreachable!("unexpected opponent choice {other}"),
-            };
-
-            let needed_result = match my_char {
-                'X' => GameRoundResult::Winner(Player::Opponent),
-                'Y' => GameRoundResult::Tie,
-                'Z' => GameRoundResult::Winner(Player::Me),
-                other => unreachable!("unexpected self choice {other}"),
-            };
-
-            RoundPartTwo {
-                needed_result,
-                opponent_choice,
-            }
-        },
-    )(input)
-}
-
-fn game_part_two(input: &str) -> IResult<&str, Vec<RoundPartTwo>> {
-    terminated(separated_list1(newline, line_part_two), opt(newline))(input)
+pub fn solve_part_one(guide: &[Round]) -> Result<u32> {
+    Ok(guide
+        .iter()
+        .map(|round| round.as_choice().calculate_score())
+        .sum())
 }
 
-fn parse_game_part_two(input: &str) -> Result<Vec<RoundPartTwo>> {
-    let (leftover, result) = game_part_two(input).map_err(|err| err.map_input(str::to_string))?;
-
-    if !leftover.is_empty() {
-        return Err(anyhow!(
-            "expected full input stream to be parsed, but got {:?} left over",
-            leftover
-        ));
-    }
-
-    Ok(result)
+pub fn solve_part_two(guide: &[Round]) -> Result<u32> {
+    Ok(guide
+        .iter()
+        .map(|round| round.as_needed_result().calculate_score())
+        .sum())
 }
 
 pub fn part_one(input: &str) -> Result<u32> {
-    let game = parse_game_part_one(input)?;
-    Ok(game
-        .into_iter()
-        .map(|round| round.0.calculate_score())
-        .sum())
+    solve_part_one(&parse(input)?)
 }
 
 pub fn part_two(input: &str) -> Result<u32> {
-    let game = parse_game_part_two(input)?;
-    Ok(game
-        .into_iter()
-        .map(|round| {
-            let my_choice = round.calculate_my_choice();
-            let round = GameRound {
-                my_choice,
-                opponent_choice: round.opponent_choice,
-            };
-            round.calculate_score()
-        })
-        .sum())
+    solve_part_two(&parse(input)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::assert_solves;
 
     const TEST_INPUT: &str = "A Y
 B X
@@ -242,4 +235,16 @@ C Z";
         let result = part_two(INPUT).unwrap();
         assert_eq!(result, 8_295);
     }
+
+    #[test]
+    fn test_model_without_text() {
+        let guide = [Round {
+            opponent_choice: Choice::Scissors,
+            column: Column::X,
+        }];
+
+        // playing rock beats scissors, while losing to scissors means playing paper
+        assert_solves(guide.as_slice(), solve_part_one, solve_part_two, (1 + 6, 2));
+        assert_eq!(parse("C X\n").unwrap(), guide);
+    }
 }
@@ -2,17 +2,42 @@ use std::collections::HashSet;
 
 use anyhow::{anyhow, Result};
 use nom::{
-    character::complete::{alpha1, char, newline},
-    combinator::{map, opt, verify},
+    character::complete::{alpha1, newline},
+    combinator::{map, opt},
     multi::separated_list1,
-    sequence::{terminated, tuple},
+    sequence::terminated,
     IResult,
 };
 
 pub const INPUT: &str = include_str!("./input");
 
-struct Rucksack(HashSet<char>, HashSet<char>);
-struct ElfGroup(HashSet<char>, HashSet<char>, HashSet<char>);
+/// Everything in one elf's rucksack, with each item written as a letter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rucksack {
+    pub items: Vec<char>,
+}
+
+impl Rucksack {
+    fn compartments(&self) -> Result<(HashSet<char>, HashSet<char>)> {
+        if !self.items.len().is_multiple_of(2) {
+            return Err(anyhow!(
+                "expected an even number of items to split into two compartments, but got {}",
+                self.items.len()
+            ));
+        }
+
+        let (first_half, second_half) = self.items.split_at(self.items.len() / 2);
+
+        Ok((
+            first_half.iter().copied().collect(),
+            second_half.iter().copied().collect(),
+        ))
+    }
+
+    fn item_types(&self) -> HashSet<char> {
+        self.items.iter().copied().collect()
+    }
+}
 
 fn get_priority(val: char) -> Result<u8> {
     let priority = match val {
@@ -24,28 +49,38 @@ fn get_priority(val: char) -> Result<u8> {
     Ok(priority)
 }
 
+// the priority of the only item type every set has in common
+fn common_priority(sets: &[HashSet<char>]) -> Result<u32> {
+    let in_common = sets
+        .iter()
+        .skip(1)
+        .fold(sets[0].clone(), |acc, set| &acc & set);
+
+    if in_common.len() != 1 {
+        return Err(anyhow!(
+            "expected to have exactly one character in common, but got {:?}",
+            in_common
+        ));
+    }
+
+    let common_chars = in_common.into_iter().collect::<Vec<_>>();
+    let common_char = common_chars[0];
+
+    get_priority(common_char).map(u32::from)
+}
+
 fn rucksack(input: &str) -> IResult<&str, Rucksack> {
-    map(
-        verify(alpha1, |s: &str| s.chars().count() % 2 == 0),
-        |s: &str| {
-            let chars = s.chars().collect::<Vec<_>>();
-            let (first_half, second_half) = chars.split_at(chars.len() / 2);
-
-            let compartment_one = first_half.iter().copied().collect();
-            let compartment_two = second_half.iter().copied().collect();
-
-            Rucksack(compartment_one, compartment_two)
-        },
-    )(input)
+    map(alpha1, |s: &str| Rucksack {
+        items: s.chars().collect(),
+    })(input)
 }
 
-fn full_parser_part_one(input: &str) -> IResult<&str, Vec<Rucksack>> {
+fn full_parser(input: &str) -> IResult<&str, Vec<Rucksack>> {
     terminated(separated_list1(newline, rucksack), opt(newline))(input)
 }
 
-fn parse_part_one(input: &str) -> Result<Vec<Rucksack>> {
-    let (leftover, result) =
-        full_parser_part_one(input).map_err(|err| err.map_input(str::to_string))?;
+pub fn parse(input: &str) -> Result<Vec<Rucksack>> {
+    let (leftover, result) = full_parser(input).map_err(|err| err.map_input(str::to_string))?;
 
     if !leftover.is_empty() {
         return Err(anyhow!(
@@ -57,82 +92,40 @@ fn parse_part_one(input: &str) -> Result<Vec<Rucksack>> {
     Ok(result)
 }
 
-pub fn part_one(input: &str) -> Result<u32> {
-    let line_values = parse_part_one(input)?
-        .into_iter()
-        .map(|line| {
-            let in_common = (&line.0) & (&line.1);
-
-            if in_common.len() != 1 {
-                return Err(anyhow!(
-                    "expected to have exactly one character in common, but got {:?}",
-                    in_common
-                ));
-            }
-
-            let common_chars = in_common.into_iter().collect::<Vec<_>>();
-            let common_char = common_chars[0];
-
-            get_priority(common_char)
-        })
-        .collect::<Result<Vec<_>>>()?;
-
-    Ok(line_values.into_iter().map(u32::from).sum())
-}
-
-fn elf_group(input: &str) -> IResult<&str, ElfGroup> {
-    map(
-        tuple((alpha1, newline, alpha1, newline, alpha1)),
-        |(elf_one, _, elf_two, _, elf_three): (&str, _, &str, _, &str)| {
-            ElfGroup(
-                elf_one.chars().collect(),
-                elf_two.chars().collect(),
-                elf_three.chars().collect(),
-            )
-        },
-    )(input)
-}
+pub fn solve_part_one(rucksacks: &[Rucksack]) -> Result<u32> {
+    rucksacks
+        .iter()
+        .map(|rucksack| {
+            let (compartment_one, compartment_two) = rucksack.compartments()?;
 
-fn full_parser_part_two(input: &str) -> IResult<&str, Vec<ElfGroup>> {
-    separated_list1(char('\n'), elf_group)(input)
+            common_priority(&[compartment_one, compartment_two])
+        })
+        .sum()
 }
 
-fn parse_part_two(input: &str) -> Result<Vec<ElfGroup>> {
-    let (leftover, result) =
-        full_parser_part_two(input).map_err(|err| err.map_input(str::to_string))?;
+pub fn solve_part_two(rucksacks: &[Rucksack]) -> Result<u32> {
+    const GROUP_SIZE: usize = 3;
 
-    if !leftover.is_empty() {
+    if !rucksacks.len().is_multiple_of(GROUP_SIZE) {
         return Err(anyhow!(
-            "expected full input stream to be parsed, but got {:?} left over",
-            leftover
+            "expected elves in groups of {}, but got {} elves",
+            GROUP_SIZE,
+            rucksacks.len()
         ));
     }
 
-    Ok(result)
+    rucksacks
+        .chunks(GROUP_SIZE)
+        .map(|group| common_priority(&group.iter().map(Rucksack::item_types).collect::<Vec<_>>()))
+        .sum()
 }
 
-pub fn part_two(input: &str) -> Result<u32> {
-    let group_values = parse_part_two(input)?
-        .into_iter()
-        .map(|group| {
-            let in_common = (&group.0) & (&group.1);
-            let in_common = (&in_common) & (&group.2);
-
-            if in_common.len() != 1 {
-                return Err(anyhow!(
-                    "expected to have exactly one character in common, but got {:?}",
-                    in_common
-                ));
-            }
-
-            let common_chars = in_common.into_iter().collect::<Vec<_>>();
-            let common_char = common_chars[0];
-
-            get_priority(common_char)
-        })
-        .collect::<Result<Vec<_>>>()?;
+pub fn part_one(input: &str) -> Result<u32> {
+    solve_part_one(&parse(input)?)
+}
 
-    Ok(group_values.into_iter().map(u32::from).sum())
+pub fn part_two(input: &str) -> Result<u32> {
+    solve_part_two(&parse(input)?)
 }
 
 #[cfg(test)]
@@ -169,4 +162,12 @@ CrZsJsPPZsGzwwsLwLmpwMDw";
         let result = part_two(INPUT).unwrap();
         assert_eq!(result, 2_825);
     }
+
+    #[test]
+    fn test_uneven_input() {
+        let rucksacks = parse(TEST_INPUT).unwrap();
+
+        assert!(solve_part_one(&parse("abc").unwrap()).is_err());
+        assert!(solve_part_two(&rucksacks[..4]).is_err());
+    }
 }
@@ -10,14 +10,15 @@ use nom::{
 
 pub const INPUT: &str = include_str!("./input");
 
-#[derive(Debug)]
-struct ElfAssignment {
-    start: u8,
-    end: u8,
+/// The sections from `start` to `end` inclusive, which one elf has to clean up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElfAssignment {
+    pub start: u8,
+    pub end: u8,
 }
 
-#[derive(Debug)]
-struct ElfAssignmentPair(ElfAssignment, ElfAssignment);
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElfAssignmentPair(pub ElfAssignment, pub ElfAssignment);
 
 impl ElfAssignmentPair {
     fn fully_overlaps(&self) -> bool {
@@ -49,7 +50,7 @@ fn full_parser(input: &str) -> IResult<&str, Vec<ElfAssignmentPair>> {
     terminated(separated_list1(newline, assignment_pair), opt(newline))(input)
 }
 
-fn parse_assignment_pairs(input: &str) -> Result<Vec<ElfAssignmentPair>> {
+pub fn parse(input: &str) -> Result<Vec<ElfAssignmentPair>> {
     let (leftover, result) = full_parser(input).map_err(|err| err.map_input(str::to_string))?;
 
     if !leftover.is_empty() {
@@ -62,28 +63,27 @@ fn parse_assignment_pairs(input: &str) -> Result<Vec<ElfAssignmentPair>> {
     Ok(result)
 }
 
+fn count_pairs(
+    pairs: &[ElfAssignmentPair],
+    predicate: fn(&ElfAssignmentPair) -> bool,
+) -> Result<u32> {
+    u32::try_from(pairs.iter().filter(|pair| predicate(pair)).count()).map_err(anyhow::Error::from)
+}
+
+pub fn solve_part_one(assignment_pairs: &[ElfAssignmentPair]) -> Result<u32> {
+    count_pairs(assignment_pairs, ElfAssignmentPair::fully_overlaps)
+}
+
+pub fn solve_part_two(assignment_pairs: &[ElfAssignmentPair]) -> Result<u32> {
+    count_pairs(assignment_pairs, ElfAssignmentPair::has_overlap)
+}
+
 pub fn part_one(input: &str) -> Result<u32> {
-    let assignment_pairs = parse_assignment_pairs(input)?;
-
-    u32::try_from(
-        assignment_pairs
-            .into_iter()
-            .filter(ElfAssignmentPair::fully_overlaps)
-            .count(),
-    )
-    .map_err(anyhow::Error::from)
+    solve_part_one(&parse(input)?)
 }
 
 pub fn part_two(input: &str) -> Result<u32> {
-    let assignment_pairs = parse_assignment_pairs(input)?;
-
-    u32::try_from(
-        assignment_pairs
-            .into_iter()
-            .filter(ElfAssignmentPair::has_overlap)
-            .count(),
-    )
-    .map_err(anyhow::Error::from)
+    solve_part_two(&parse(input)?)
 }
 
 #[cfg(test)]
@@ -9,17 +9,18 @@ use nom::{
     IResult, Parser,
 };
 
-#[derive(Debug)]
-struct Move {
-    start_idx: usize,
-    end_idx: usize,
-    count: usize,
+/// Moves `count` crates from the top of one stack to another, with stacks numbered from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub start_idx: usize,
+    pub end_idx: usize,
+    pub count: usize,
 }
 
-#[derive(Debug)]
-struct ProblemStatement {
-    stacks: Vec<Vec<char>>, // [stack_idx][height], with 0-indexed height
-    moves: Vec<Move>,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rearrangement {
+    pub stacks: Vec<Vec<char>>, // [stack_idx][height], with 0-indexed height
+    pub moves: Vec<Move>,
 }
 
 pub const INPUT: &str = include_str!("./input");
@@ -78,7 +79,7 @@ fn parse_moves(input: &str) -> IResult<&str, Vec<Move>> {
     separated_list1(newline, parse_move)(input)
 }
 
-fn parse_problem_statement(input: &str) -> Result<ProblemStatement> {
+pub fn parse(input: &str) -> Result<Rearrangement> {
     let rest = input;
     let (rest, stacks) = parse_stacks(rest).map_err(|err| err.map_input(str::to_string))?;
     let (rest, _) = parse_stacks_labels(rest).map_err(|err| err.map_input(str::to_string))?;
@@ -91,23 +92,34 @@ fn parse_problem_statement(input: &str) -> Result<ProblemStatement> {
         ));
     }
 
-    Ok(ProblemStatement { moves, stacks })
+    Ok(Rearrangement { moves, stacks })
 }
 
-pub fn part_one(input: &str) -> Result<String> {
-    let problem = parse_problem_statement(input)?;
+// the indices of the stacks a move takes crates from and to
+fn stack_indices(stacks: &[Vec<char>], problem_move: &Move) -> Result<(usize, usize)> {
+    let idx = |label: usize| {
+        label
+            .checked_sub(1)
+            .filter(|&idx| idx < stacks.len())
+            .ok_or_else(|| anyhow!("there is no stack {} in {:?}", label, problem_move))
+    };
+
+    Ok((idx(problem_move.start_idx)?, idx(problem_move.end_idx)?))
+}
 
-    let mut stacks = problem.stacks;
-    for problem_move in problem.moves {
+pub fn solve_part_one(rearrangement: &Rearrangement) -> Result<String> {
+    let mut stacks = rearrangement.stacks.clone();
+    for problem_move in &rearrangement.moves {
+        let (start_idx, end_idx) = stack_indices(&stacks, problem_move)?;
         for _ in 0..problem_move.count {
-            let moved = stacks[problem_move.start_idx - 1].pop().ok_or_else(|| {
+            let moved = stacks[start_idx].pop().ok_or_else(|| {
                 anyhow!(
                     "attempted to move from empty stack {}: {:?}",
                     problem_move.start_idx,
                     stacks
                 )
             })?;
-            stacks[problem_move.end_idx - 1].push(moved);
+            stacks[end_idx].push(moved);
         }
     }
 
@@ -119,14 +131,13 @@ pub fn part_one(input: &str) -> Result<String> {
     Ok(solution)
 }
 
-pub fn part_two(input: &str) -> Result<String> {
-    let problem = parse_problem_statement(input)?;
-
-    let mut stacks = problem.stacks;
-    for problem_move in problem.moves {
+pub fn solve_part_two(rearrangement: &Rearrangement) -> Result<String> {
+    let mut stacks = rearrangement.stacks.clone();
+    for problem_move in &rearrangement.moves {
+        let (start_idx, end_idx) = stack_indices(&stacks, problem_move)?;
         let mut scratch = Vec::new();
         for _ in 0..problem_move.count {
-            let moved = stacks[problem_move.start_idx - 1].pop().ok_or_else(|| {
+            let moved = stacks[start_idx].pop().ok_or_else(|| {
                 anyhow!(
                     "attempted to move from empty stack {}: {:?}",
                     problem_move.start_idx,
@@ -137,7 +148,7 @@ pub fn part_two(input: &str) -> Result<String> {
         }
 
         while let Some(moved) = scratch.pop() {
-            stacks[problem_move.end_idx - 1].push(moved);
+            stacks[end_idx].push(moved);
         }
     }
 
@@ -149,9 +160,18 @@ pub fn part_two(input: &str) -> Result<String> {
     Ok(solution)
 }
 
+pub fn part_one(input: &str) -> Result<String> {
+    solve_part_one(&parse(input)?)
+}
+
+pub fn part_two(input: &str) -> Result<String> {
+    solve_part_two(&parse(input)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::assert_solves;
 
     const TEST_INPUT: &str = "    [D]    
 [N] [C]    
@@ -186,4 +206,26 @@ move 1 from 1 to 2";
         let result = part_two(INPUT).unwrap();
         assert_eq!(result, "NHWZCBNBF");
     }
+
+    #[test]
+    fn test_model_without_text() {
+        let mut rearrangement = Rearrangement {
+            stacks: vec![vec!['A', 'B'], vec!['C']],
+            moves: vec![Move {
+                start_idx: 1,
+                end_idx: 2,
+                count: 2,
+            }],
+        };
+
+        assert_solves(
+            &rearrangement,
+            solve_part_one,
+            solve_part_two,
+            ("A".to_string(), "B".to_string()),
+        );
+
+        rearrangement.moves[0].end_idx = 3;
+        assert!(solve_part_one(&rearrangement).is_err());
+    }
 }
@@ -4,8 +4,20 @@ use anyhow::{anyhow, Error, Result};
 
 pub const INPUT: &str = include_str!("./input");
 
-fn calculate_solution(input: &str, num_needed: usize) -> Result<u32> {
-    let chars = input.chars().collect::<Vec<_>>();
+/// The datastream buffer, one character at a time.
+pub fn parse(input: &str) -> Result<Vec<char>> {
+    Ok(input.trim_end().chars().collect())
+}
+
+fn calculate_solution(chars: &[char], num_needed: usize) -> Result<u32> {
+    if chars.len() < num_needed {
+        return Err(anyhow!(
+            "expected at least {} characters to look for a marker in, but got {}",
+            num_needed,
+            chars.len()
+        ));
+    }
+
     let mut seen: BTreeMap<char, u32> = BTreeMap::new();
     for c in chars[..num_needed].iter().copied() {
         *seen.entry(c).or_insert(0) += 1;
@@ -33,16 +45,24 @@ fn calculate_solution(input: &str, num_needed: usize) -> Result<u32> {
     ))
 }
 
-pub fn part_one(input: &str) -> Result<u32> {
+pub fn solve_part_one(datastream: &[char]) -> Result<u32> {
     const NUM_DISTINCT: usize = 4;
 
-    calculate_solution(input, NUM_DISTINCT)
+    calculate_solution(datastream, NUM_DISTINCT)
 }
 
-pub fn part_two(input: &str) -> Result<u32> {
+pub fn solve_part_two(datastream: &[char]) -> Result<u32> {
     const NUM_DISTINCT: usize = 14;
 
-    calculate_solution(input, NUM_DISTINCT)
+    calculate_solution(datastream, NUM_DISTINCT)
+}
+
+pub fn part_one(input: &str) -> Result<u32> {
+    solve_part_one(&parse(input)?)
+}
+
+pub fn part_two(input: &str) -> Result<u32> {
+    solve_part_two(&parse(input)?)
 }
 
 #[cfg(test)]
@@ -123,6 +143,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_too_short() {
+        for input in ["", "\n", "abc", "abc\n"] {
+            let err = part_one(input).unwrap_err();
+            assert!(
+                err.to_string()
+                    .starts_with("expected at least 4 characters"),
+                "{err}"
+            );
+        }
+
+        let err = solve_part_two(&parse("abcdefghijklm").unwrap()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected at least 14 characters to look for a marker in, but got 13"
+        );
+    }
+
     #[test]
     fn solution_part_two() {
         let result = part_two(INPUT).unwrap();
@@ -0,0 +1,389 @@
+use std::collections::{BTreeMap, HashSet};
+
+use anyhow::{anyhow, Result};
+
+use super::Fs;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Delete the single smallest directory that frees up enough space, as the puzzle asks.
+    SmallestDirectory,
+    /// Delete the set of non-nested directories with the smallest total size that frees up enough
+    /// space.
+    SmallestTotal,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CleanupPlan {
+    /// How much space had to be freed; zero when there was already enough free space.
+    pub needed: u64,
+    /// Absolute paths of the directories to delete, in walk order.
+    pub directories: Vec<String>,
+    /// Total size of `directories`.
+    pub freed: u64,
+    /// Whether no other plan for the strategy frees up less. [`Strategy::SmallestTotal`] only
+    /// keeps so many totals in memory, and on filesystems that need more it settles for the best
+    /// plan found before running out, which may be the single smallest directory.
+    pub optimal: bool,
+}
+
+// how many totals `Strategy::SmallestTotal` keeps in memory at once, across all of its lists
+const MAXIMUM_TOTALS: usize = 1 << 24;
+
+/// Plans which directories to delete so that a disk of size `capacity` has at least
+/// `required_free` space available.
+pub fn plan(fs: &Fs, capacity: u64, required_free: u64, strategy: Strategy) -> Result<CleanupPlan> {
+    let used = fs.total_size();
+    if used > capacity {
+        return Err(anyhow!(
+            "filesystem uses {} but the disk only has a capacity of {}",
+            used,
+            capacity
+        ));
+    }
+
+    if required_free > capacity {
+        return Err(anyhow!(
+            "can't free up {} on a disk with a capacity of {}",
+            required_free,
+            capacity
+        ));
+    }
+
+    let currently_free = capacity - used;
+    let Some(needed) = required_free
+        .checked_sub(currently_free)
+        .filter(|&needed| needed > 0)
+    else {
+        return Ok(CleanupPlan {
+            needed: 0,
+            directories: Vec::new(),
+            freed: 0,
+            optimal: true,
+        });
+    };
+
+    // deleting `/` always frees up everything, so there is always at least one candidate
+    let (smallest_path, smallest_size) = fs
+        .walk()
+        .filter(|entry| entry.node.is_dir())
+        .map(|entry| (entry.path, entry.node.size()))
+        .filter(|&(_, size)| size >= needed)
+        .min_by_key(|&(_, size)| size)
+        .ok_or_else(|| anyhow!("couldn't find any directories freeing up at least {needed}"))?;
+
+    let single = CleanupPlan {
+        needed,
+        directories: vec![smallest_path],
+        freed: smallest_size,
+        optimal: true,
+    };
+
+    match strategy {
+        Strategy::SmallestDirectory => Ok(single),
+        Strategy::SmallestTotal => Ok(smallest_total(fs, single, MAXIMUM_TOTALS)),
+    }
+}
+
+// Tree knapsack over the directories in pre-order, where deleting a directory skips its whole
+// subtree. `reachable[i]` is the set of totals that can be freed using only the directories from
+// position `i` onwards, so `reachable[i] = reachable[i + 1] | (reachable[end[i]] + size[i])`.
+// The sets are sorted lists of the totals that actually come up, never holding anything above the
+// best total found so far, as freeing up more than that never helps. Only the sets still referred
+// to by an unvisited ancestor are kept around; to recover the chosen directories afterwards we
+// instead remember which totals each position added. Once all of those together hold more than
+// `maximum_totals` totals, this gives up on finding the best plan and settles for the best one so
+// far, or `single` if that's still the best.
+fn smallest_total(fs: &Fs, single: CleanupPlan, maximum_totals: usize) -> CleanupPlan {
+    let needed = single.needed;
+    let order = pre_order_dirs(fs);
+    let ends = subtree_ends(fs, &order);
+
+    // the positions whose sets an ancestor will need once it gets there
+    let referred_to = ends
+        .iter()
+        .enumerate()
+        .filter(|&(position, &end)| end != position + 1)
+        .map(|(_, &end)| end)
+        .collect::<HashSet<_>>();
+
+    // `added[i]` holds the totals in `reachable[i]` but not in `reachable[i + 1]`, in order
+    let mut added = vec![Vec::new(); order.len() + 1];
+    added[order.len()].push(0);
+    // totals above the best single directory are never useful
+    let mut best = single.freed;
+    let mut optimal = true;
+
+    let mut current = vec![0u64];
+    let mut saved = BTreeMap::from([(order.len(), current.clone())]);
+    let mut first_position = order.len();
+    // how many totals `added` and `saved` hold between them
+    let mut num_kept = 2;
+
+    for (position, &idx) in order.iter().enumerate().rev() {
+        let size = fs.dir(idx).size();
+
+        let after_subtree = if ends[position] == position + 1 {
+            &current
+        } else {
+            &saved[&ends[position]]
+        };
+        let bound = best;
+        let with_this = after_subtree
+            .iter()
+            .map(|&total| total.saturating_add(size))
+            .take_while(|&total| total <= bound);
+
+        let mut merged = Vec::with_capacity(current.len() + after_subtree.len());
+        let mut existing = current.iter().copied().peekable();
+        for total in with_this {
+            while let Some(smaller) = existing.next_if(|&other| other < total) {
+                merged.push(smaller);
+            }
+
+            if existing.next_if_eq(&total).is_none() {
+                added[position].push(total);
+                if total >= needed {
+                    best = best.min(total);
+                }
+            }
+            merged.push(total);
+        }
+        merged.extend(existing);
+        merged.truncate(merged.partition_point(|&total| total <= best));
+        num_kept += added[position].len();
+
+        current = merged;
+        first_position = position;
+
+        // nothing frees up less than exactly what's needed
+        if added[position].binary_search(&needed).is_ok() {
+            break;
+        }
+
+        // nothing before this position can jump into its subtree, only past it
+        saved.retain(|&saved_position, totals| {
+            let keep = saved_position >= ends[position];
+            if !keep {
+                num_kept -= totals.len();
+            }
+            keep
+        });
+        if referred_to.contains(&position) {
+            num_kept += current.len();
+            saved.insert(position, current.clone());
+        }
+
+        if num_kept + current.len() > maximum_totals {
+            optimal = false;
+            break;
+        }
+    }
+
+    // the best total so far is only reachable from `first_position` if it was found there or
+    // after it, and the single directory may not have been visited yet
+    if !optimal && best == single.freed {
+        return CleanupPlan {
+            optimal: false,
+            ..single
+        };
+    }
+
+    // walk forwards, skipping a directory whenever the remaining total is reachable without it
+    let mut directories = Vec::new();
+    let mut remaining = best;
+    let mut position = first_position;
+    while remaining > 0 {
+        if added[position].binary_search(&remaining).is_err() {
+            position += 1;
+        } else {
+            let idx = order[position];
+            directories.push(fs.path_of(idx));
+            remaining -= fs.dir(idx).size();
+            position = ends[position];
+        }
+    }
+
+    CleanupPlan {
+        needed,
+        directories,
+        freed: best,
+        optimal,
+    }
+}
+
+fn pre_order_dirs(fs: &Fs) -> Vec<usize> {
+    let mut result = Vec::new();
+    let mut stack = vec![Fs::ROOT_IDX];
+    while let Some(idx) = stack.pop() {
+        result.push(idx);
+
+        // push in reverse so that children are popped in name order, matching `Fs::walk`
+        let child_dirs = fs.child_dirs(fs.dir(idx)).collect::<Vec<_>>();
+        stack.extend(child_dirs.into_iter().rev());
+    }
+
+    result
+}
+
+// for every position in the pre-order, the position just past the end of its subtree
+fn subtree_ends(fs: &Fs, order: &[usize]) -> Vec<usize> {
+    let mut subtree_sizes = vec![1; fs.nodes.len()];
+    for &idx in order.iter().rev() {
+        let dir = fs.dir(idx);
+        if idx != Fs::ROOT_IDX {
+            subtree_sizes[dir.parent_idx] += subtree_sizes[idx];
+        }
+    }
+
+    order
+        .iter()
+        .enumerate()
+        .map(|(position, &idx)| position + subtree_sizes[idx])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::TEST_INPUT;
+    use super::*;
+
+    #[test]
+    fn test_smallest_directory() {
+        let fs = Fs::from_transcript(TEST_INPUT).unwrap();
+
+        let result = plan(&fs, 70_000_000, 30_000_000, Strategy::SmallestDirectory).unwrap();
+        let expected = CleanupPlan {
+            needed: 8_381_165,
+            directories: vec!["/d".to_string()],
+            freed: 24_933_642,
+            optimal: true,
+        };
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_already_enough_space() {
+        let fs = Fs::from_transcript(TEST_INPUT).unwrap();
+
+        for strategy in [Strategy::SmallestDirectory, Strategy::SmallestTotal] {
+            let result = plan(&fs, 100_000_000, 30_000_000, strategy).unwrap();
+            assert_eq!(result.needed, 0);
+            assert!(result.directories.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_impossible_targets() {
+        let fs = Fs::from_transcript(TEST_INPUT).unwrap();
+
+        // more used than the disk can hold
+        assert!(plan(&fs, 1_000, 0, Strategy::SmallestDirectory).is_err());
+        // more free space asked for than the disk can hold
+        assert!(plan(&fs, 70_000_000, 80_000_000, Strategy::SmallestTotal).is_err());
+    }
+
+    #[test]
+    fn test_smallest_total() {
+        let fs = Fs::from_transcript(
+            "$ cd /
+$ ls
+dir a
+dir b
+dir c
+$ cd a
+$ ls
+dir x
+dir y
+$ cd x
+$ ls
+40 x.txt
+$ cd ../y
+$ ls
+35 y.txt
+$ cd /b
+$ ls
+50 b.txt
+$ cd /c
+$ ls
+100 c.txt",
+        )
+        .unwrap();
+
+        // 225 used leaves 75 free, so 80 more is needed
+        let single = plan(&fs, 300, 155, Strategy::SmallestDirectory).unwrap();
+        assert_eq!(single.needed, 80);
+        assert_eq!(single.directories, ["/c"]);
+        assert_eq!(single.freed, 100);
+
+        let total = plan(&fs, 300, 155, Strategy::SmallestTotal).unwrap();
+        assert_eq!(total.needed, 80);
+        assert_eq!(total.directories, ["/a/y", "/b"]);
+        assert_eq!(total.freed, 85);
+        assert!(total.optimal);
+
+        // with too little room for every total, it settles for a plan that frees up enough
+        for maximum_totals in 0..20 {
+            let limited = smallest_total(&fs, single.clone(), maximum_totals);
+            assert!(limited.freed >= 80 && limited.freed <= 100);
+            let deleted_size = limited
+                .directories
+                .iter()
+                .map(|path| fs.get(path).unwrap().size())
+                .sum::<u64>();
+            assert_eq!(deleted_size, limited.freed);
+            if limited.optimal {
+                assert_eq!(limited, total);
+            }
+        }
+        assert_eq!(
+            smallest_total(&fs, single.clone(), 0),
+            CleanupPlan {
+                optimal: false,
+                ..single
+            }
+        );
+    }
+
+    #[test]
+    fn test_smallest_total_large_sizes() {
+        const GIB: u64 = 1 << 30;
+
+        // the same tree as above, with sizes in GiB rather than bytes
+        let mut fs = Fs::new();
+        for (parent, name) in [("/", "a"), ("/", "b"), ("/", "c"), ("/a", "x"), ("/a", "y")] {
+            fs.add_dir(parent, name).unwrap();
+        }
+        for (parent, name, size) in [
+            ("/a/x", "x.txt", 40),
+            ("/a/y", "y.txt", 35),
+            ("/b", "b.txt", 50),
+            ("/c", "c.txt", 100),
+        ] {
+            fs.add_file(parent, name, size * GIB).unwrap();
+        }
+
+        let total = plan(&fs, 300 * GIB, 155 * GIB, Strategy::SmallestTotal).unwrap();
+        assert_eq!(total.directories, ["/a/y", "/b"]);
+        assert_eq!(total.freed, 85 * GIB);
+        assert!(total.optimal);
+    }
+
+    #[test]
+    fn solution_smallest_total() {
+        let fs = Fs::from_transcript(super::super::INPUT).unwrap();
+
+        let single = plan(&fs, 70_000_000, 30_000_000, Strategy::SmallestDirectory).unwrap();
+        let total = plan(&fs, 70_000_000, 30_000_000, Strategy::SmallestTotal).unwrap();
+
+        assert!(total.optimal);
+        assert!(total.freed >= total.needed);
+        assert!(total.freed <= single.freed);
+        let deleted_size = total
+            .directories
+            .iter()
+            .map(|path| fs.get(path).unwrap().size())
+            .sum::<u64>();
+        assert_eq!(deleted_size, total.freed);
+    }
+}
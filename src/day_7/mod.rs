@@ -4,12 +4,15 @@ use anyhow::{anyhow, Result};
 use nom::{
     branch::alt,
     bytes::complete::{is_not, tag},
-    character::{complete::newline, streaming::u32},
-    multi::separated_list1,
+    character::complete::{newline, u64},
+    multi::{many0, separated_list1},
     sequence::{preceded, separated_pair},
     IResult,
 };
 
+pub mod cleanup;
+pub mod report;
+
 pub const INPUT: &str = include_str!("./input");
 
 #[derive(Debug)]
@@ -26,7 +29,7 @@ enum CommandExecution {
 #[derive(Debug)]
 enum FileInfo {
     Directory(String),
-    File { name: String, size: u32 },
+    File { name: String, size: u64 },
 }
 
 fn file_info(input: &str) -> IResult<&str, FileInfo> {
@@ -38,7 +41,7 @@ fn file_info(input: &str) -> IResult<&str, FileInfo> {
     }
 
     fn file(input: &str) -> IResult<&str, FileInfo> {
-        let (rest, (size, name)) = separated_pair(u32, tag(" "), is_not("\n"))(input)?;
+        let (rest, (size, name)) = separated_pair(u64, tag(" "), is_not("\n"))(input)?;
 
         let info = FileInfo::File {
             name: name.to_string(),
@@ -61,7 +64,8 @@ fn command_execution(input: &str) -> IResult<&str, CommandExecution> {
     }
 
     fn ls(input: &str) -> IResult<&str, CommandExecution> {
-        let (rest, files) = preceded(tag("$ ls\n"), separated_list1(tag("\n"), file_info))(input)?;
+        // an empty directory lists nothing, so the output lines are all optional
+        let (rest, files) = preceded(tag("$ ls"), many0(preceded(newline, file_info)))(input)?;
 
         let execution = CommandExecution::Ls(files);
 
@@ -94,180 +98,456 @@ fn parse_problem_statement(input: &str) -> Result<ProblemStatement> {
 }
 
 #[derive(Debug)]
-struct DirNode {
+pub struct DirNode {
     name: String,
     children_idx: BTreeMap<String, usize>,
     parent_idx: usize,
+    size: u64, // cached total of everything beneath this directory
+}
+
+impl DirNode {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
 }
 
 #[derive(Debug)]
-struct FileNode {
+pub struct FileNode {
     name: String,
-    size: u32,
+    size: u64,
+    parent_idx: usize,
+}
+
+impl FileNode {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
 }
 
 #[derive(Debug)]
-enum FsNode {
+pub enum FsNode {
     Dir(DirNode),
     File(FileNode),
 }
 
 impl FsNode {
-    fn name(&self) -> &str {
+    pub fn name(&self) -> &str {
         match &self {
             FsNode::Dir(DirNode { name, .. }) => name,
             FsNode::File(FileNode { name, .. }) => name,
         }
     }
+
+    pub fn is_dir(&self) -> bool {
+        matches!(self, FsNode::Dir(_))
+    }
+
+    /// Total size of this node: a file's own size, or the sum of everything beneath a directory.
+    pub fn size(&self) -> u64 {
+        match self {
+            FsNode::Dir(dir) => dir.size(),
+            FsNode::File(file) => file.size(),
+        }
+    }
 }
 
+/// A single node visited by [`Fs::walk`], along with its absolute path and depth below `/`.
 #[derive(Debug)]
-struct Fs {
+pub struct WalkEntry<'a> {
+    pub path: String,
+    pub depth: usize,
+    pub node: &'a FsNode,
+}
+
+/// Depth-first, pre-order iterator over every node in an [`Fs`]. Children are visited in name order.
+#[derive(Debug)]
+pub struct Walk<'a> {
+    fs: &'a Fs,
+    stack: Vec<(usize, usize, String)>, // (node_idx, depth, path)
+}
+
+impl<'a> Iterator for Walk<'a> {
+    type Item = WalkEntry<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (idx, depth, path) = self.stack.pop()?;
+        let node = &self.fs.nodes[idx];
+
+        if let FsNode::Dir(DirNode { children_idx, .. }) = node {
+            // push in reverse so that children are popped in name order
+            for (name, &child_idx) in children_idx.iter().rev() {
+                self.stack
+                    .push((child_idx, depth + 1, Fs::join_path(&path, name)));
+            }
+        }
+
+        Some(WalkEntry { path, depth, node })
+    }
+}
+
+#[derive(Debug)]
+pub struct Fs {
     nodes: Vec<FsNode>,
     current_directory_idx: usize,
 }
 
+impl Default for Fs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Fs {
-    fn new() -> Self {
+    const ROOT_IDX: usize = 0;
+
+    pub fn new() -> Self {
         let root_node = FsNode::Dir(DirNode {
             name: "/".to_string(),
             children_idx: BTreeMap::new(),
-            parent_idx: 0,
+            parent_idx: Self::ROOT_IDX,
+            size: 0,
         });
 
         Self {
             nodes: vec![root_node],
-            current_directory_idx: 0,
+            current_directory_idx: Self::ROOT_IDX,
         }
     }
 
-    fn cd(&mut self, path: &str) {
-        if path == "/" {
-            self.current_directory_idx = 0;
-            return;
+    /// Reconstructs a filesystem by replaying the `cd` and `ls` commands of a terminal transcript.
+    pub fn from_transcript(input: &str) -> Result<Self> {
+        let problem_statement = parse_problem_statement(input)?;
+
+        let mut fs = Self::new();
+        for command in problem_statement.commands {
+            match command {
+                CommandExecution::Cd(path) => fs.cd(&path)?,
+                CommandExecution::Ls(files) => {
+                    for file_info in files {
+                        fs.create_file(file_info)?;
+                    }
+                }
+            }
         }
 
-        let FsNode::Dir(current_directory) = &self.nodes[self.current_directory_idx] else {
-            unreachable!("current directory was a somehow not a directory: {:?}", self);
-        };
+        Ok(fs)
+    }
+
+    /// Adds an empty directory called `name` inside the directory at `parent`, which is resolved
+    /// like a `cd` from `/`. Adding a directory that already exists does nothing.
+    pub fn add_dir(&mut self, parent: &str, name: &str) -> Result<()> {
+        self.create_in(parent, FileInfo::Directory(name.to_string()))
+    }
+
+    /// Adds a file of `size` bytes called `name` inside the directory at `parent`, which is
+    /// resolved like a `cd` from `/`. Adding the same file again with the same size does nothing.
+    pub fn add_file(&mut self, parent: &str, name: &str, size: u64) -> Result<()> {
+        self.create_in(
+            parent,
+            FileInfo::File {
+                name: name.to_string(),
+                size,
+            },
+        )
+    }
+
+    // creates a file or directory in `parent` without moving the current directory
+    fn create_in(&mut self, parent: &str, file_info: FileInfo) -> Result<()> {
+        let previous_directory_idx = self.current_directory_idx;
+        self.current_directory_idx = Self::ROOT_IDX;
 
-        if path == ".." {
-            self.current_directory_idx = current_directory.parent_idx;
-            return;
+        let result = self.cd(parent).and_then(|()| self.create_file(file_info));
+
+        self.current_directory_idx = previous_directory_idx;
+        result
+    }
+
+    fn cd(&mut self, path: &str) -> Result<()> {
+        let new_directory_idx = self.resolve(self.current_directory_idx, path)?;
+
+        if !self.nodes[new_directory_idx].is_dir() {
+            return Err(anyhow!(
+                "tried to cd into {:?}, which is a file and not a directory",
+                path
+            ));
         }
 
-        if let Some(&new_directory_idx) = current_directory.children_idx.get(path) {
-            self.current_directory_idx = new_directory_idx;
+        self.current_directory_idx = new_directory_idx;
+        Ok(())
+    }
+
+    // resolves an absolute or relative (to `start_idx`) path, which may contain `.` and `..`
+    fn resolve(&self, start_idx: usize, path: &str) -> Result<usize> {
+        let mut current_idx = if path.starts_with('/') {
+            Self::ROOT_IDX
         } else {
-            unreachable!(
-                "tried to cd to directory that didn't exist: {:?}, {:?}",
-                path, self
-            );
+            start_idx
+        };
+
+        for segment in path.split('/') {
+            let FsNode::Dir(current_directory) = &self.nodes[current_idx] else {
+                return Err(anyhow!(
+                    "path {:?} passes through {:?}, which is a file and not a directory",
+                    path,
+                    self.path_of(current_idx)
+                ));
+            };
+
+            current_idx = match segment {
+                "" | "." => current_idx,
+                ".." => current_directory.parent_idx,
+                name => *current_directory.children_idx.get(name).ok_or_else(|| {
+                    anyhow!(
+                        "path {:?} refers to {:?}, which doesn't exist in {:?}",
+                        path,
+                        name,
+                        self.path_of(current_idx)
+                    )
+                })?,
+            };
         }
+
+        Ok(current_idx)
     }
 
-    fn create_file(&mut self, file_info: FileInfo) {
+    fn create_file(&mut self, file_info: FileInfo) -> Result<()> {
         let new_idx = self.nodes.len();
 
-        let FsNode::Dir(current_directory) = &mut self.nodes[self.current_directory_idx] else {
-            unreachable!("current directory was a somehow not a directory: {:?}", self);
+        let FsNode::Dir(current_directory) = &self.nodes[self.current_directory_idx] else {
+            unreachable!(
+                "current directory was a somehow not a directory: {:?}",
+                self
+            );
+        };
+
+        let new_filename = match &file_info {
+            FileInfo::Directory(name) | FileInfo::File { name, .. } => name,
         };
 
+        // listing the same directory more than once must agree with what was seen before
+        if let Some(&existing_idx) = current_directory.children_idx.get(new_filename) {
+            let path = self.path_of(existing_idx);
+            return match (&self.nodes[existing_idx], &file_info) {
+                (FsNode::Dir(_), FileInfo::Directory(_)) => Ok(()),
+                (FsNode::File(existing), FileInfo::File { size, .. }) if existing.size == *size => {
+                    Ok(())
+                }
+                (FsNode::File(existing), FileInfo::File { size, .. }) => Err(anyhow!(
+                    "file {:?} was listed with size {}, but was previously listed with size {}",
+                    path,
+                    size,
+                    existing.size
+                )),
+                (FsNode::Dir(_), FileInfo::File { .. }) => Err(anyhow!(
+                    "{:?} was listed as a file, but was previously listed as a directory",
+                    path
+                )),
+                (FsNode::File(_), FileInfo::Directory(_)) => Err(anyhow!(
+                    "{:?} was listed as a directory, but was previously listed as a file",
+                    path
+                )),
+            };
+        }
+
         let new_file = match file_info {
             FileInfo::Directory(name) => FsNode::Dir(DirNode {
                 name,
                 children_idx: BTreeMap::new(),
                 parent_idx: self.current_directory_idx,
+                size: 0,
+            }),
+            FileInfo::File { name, size } => FsNode::File(FileNode {
+                name,
+                size,
+                parent_idx: self.current_directory_idx,
             }),
-            FileInfo::File { name, size } => FsNode::File(FileNode { name, size }),
         };
         let new_filename = new_file.name().to_string();
+        let added_size = new_file.size();
+
+        self.propagate_size(self.current_directory_idx, added_size)
+            .map_err(|err| {
+                let path =
+                    Self::join_path(&self.path_of(self.current_directory_idx), &new_filename);
+                anyhow!("can't add {:?}: {}", path, err)
+            })?;
+
+        let FsNode::Dir(current_directory) = &mut self.nodes[self.current_directory_idx] else {
+            unreachable!(
+                "current directory was a somehow not a directory: {:?}",
+                self
+            );
+        };
 
         current_directory.children_idx.insert(new_filename, new_idx);
         self.nodes.push(new_file);
+
+        Ok(())
+    }
+
+    fn dir(&self, idx: usize) -> &DirNode {
+        let FsNode::Dir(dir) = &self.nodes[idx] else {
+            unreachable!("expected node {} to be a directory: {:?}", idx, self);
+        };
+
+        dir
+    }
+
+    // the indices of the directories directly inside `dir`, in name order
+    fn child_dirs<'a>(&'a self, dir: &'a DirNode) -> impl Iterator<Item = usize> + 'a {
+        dir.children_idx
+            .values()
+            .copied()
+            .filter(|&child_idx| self.nodes[child_idx].is_dir())
     }
 
-    fn calculate_size(&self, file_idx: usize) -> u32 {
-        match &self.nodes[file_idx] {
-            FsNode::File(FileNode { size, .. }) => *size,
-            FsNode::Dir(DirNode { children_idx, .. }) => children_idx
-                .values()
-                .map(|child_idx| self.calculate_size(*child_idx))
-                .sum(),
+    fn path_of(&self, idx: usize) -> String {
+        let mut segments = Vec::new();
+        let mut current_idx = idx;
+        while current_idx != Self::ROOT_IDX {
+            let node = &self.nodes[current_idx];
+            segments.push(node.name());
+            current_idx = match node {
+                FsNode::Dir(DirNode { parent_idx, .. }) => *parent_idx,
+                FsNode::File(FileNode { parent_idx, .. }) => *parent_idx,
+            };
         }
+
+        segments.reverse();
+        format!("/{}", segments.join("/"))
     }
-}
 
-pub fn part_one(input: &str) -> Result<u32> {
-    const MAXIMUM_DIRECTORY_SIZE: u32 = 100_000;
-
-    let problem_statement = parse_problem_statement(input)?;
-    let mut fs = Fs::new();
-    for command in problem_statement.commands {
-        match command {
-            CommandExecution::Cd(path) => fs.cd(&path),
-            CommandExecution::Ls(files) => {
-                for file_info in files {
-                    fs.create_file(file_info);
-                }
+    // keep cached directory sizes up to date by adding to every directory from `dir_idx` up to `/`.
+    // No directory is bigger than `/`, so checking `/` first means none of them can overflow, and
+    // nothing changes when one would.
+    fn propagate_size(&mut self, dir_idx: usize, added_size: u64) -> Result<()> {
+        let root_size = self.root().size;
+        if root_size.checked_add(added_size).is_none() {
+            return Err(anyhow!(
+                "the total size of / would overflow, adding {} to {}",
+                added_size,
+                root_size
+            ));
+        }
+
+        let mut current_idx = dir_idx;
+        loop {
+            let FsNode::Dir(dir) = &mut self.nodes[current_idx] else {
+                unreachable!("parent was somehow not a directory: {:?}", self);
+            };
+
+            dir.size += added_size;
+
+            if current_idx == Self::ROOT_IDX {
+                break;
             }
+            current_idx = dir.parent_idx;
+        }
+
+        Ok(())
+    }
+
+    fn join_path(parent: &str, name: &str) -> String {
+        if parent.ends_with('/') {
+            format!("{parent}{name}")
+        } else {
+            format!("{parent}/{name}")
         }
     }
 
+    pub fn root(&self) -> &DirNode {
+        let FsNode::Dir(root) = &self.nodes[Self::ROOT_IDX] else {
+            unreachable!("root was somehow not a directory: {:?}", self);
+        };
+
+        root
+    }
+
+    /// Looks up a node by absolute path, such as `/a/e/i` or `/a/e/../f`. Returns `None` for
+    /// relative paths or paths that don't exist.
+    pub fn get(&self, path: &str) -> Option<&FsNode> {
+        if !path.starts_with('/') {
+            return None;
+        }
+
+        let idx = self.resolve(Self::ROOT_IDX, path).ok()?;
+
+        Some(&self.nodes[idx])
+    }
+
+    /// Iterates over the direct children of `dir`, in name order.
+    pub fn children<'a>(&'a self, dir: &'a DirNode) -> impl Iterator<Item = &'a FsNode> + 'a {
+        dir.children_idx
+            .values()
+            .map(|&child_idx| &self.nodes[child_idx])
+    }
+
+    pub fn walk(&self) -> Walk<'_> {
+        Walk {
+            fs: self,
+            stack: vec![(Self::ROOT_IDX, 0, "/".to_string())],
+        }
+    }
+
+    pub fn total_size(&self) -> u64 {
+        self.root().size()
+    }
+}
+
+/// The filesystem the transcript explores, see [`Fs::from_transcript`].
+pub fn parse(input: &str) -> Result<Fs> {
+    Fs::from_transcript(input)
+}
+
+pub fn solve_part_one(fs: &Fs) -> Result<u64> {
+    const MAXIMUM_DIRECTORY_SIZE: u64 = 100_000;
+
     let total = fs
-        .nodes
-        .iter()
-        .enumerate()
-        .filter_map(|(i, node)| match node {
-            FsNode::File(_) => None,
-            FsNode::Dir(_) => Some(fs.calculate_size(i)),
-        })
+        .walk()
+        .filter(|entry| entry.node.is_dir())
+        .map(|entry| entry.node.size())
         .filter(|&dir_size| dir_size <= MAXIMUM_DIRECTORY_SIZE)
         .sum();
 
     Ok(total)
 }
 
-pub fn part_two(input: &str) -> Result<u32> {
-    const FS_SIZE: u32 = 70_000_000;
-    const MINIMUM_FREE_SPACE: u32 = 30_000_000;
-    const MAXIMUM_USED_SPACE: u32 = FS_SIZE - MINIMUM_FREE_SPACE;
-
-    let problem_statement = parse_problem_statement(input)?;
-    let mut fs = Fs::new();
-    for command in problem_statement.commands {
-        match command {
-            CommandExecution::Cd(path) => fs.cd(&path),
-            CommandExecution::Ls(files) => {
-                for file_info in files {
-                    fs.create_file(file_info);
-                }
-            }
-        }
-    }
+pub fn solve_part_two(fs: &Fs) -> Result<u64> {
+    const FS_SIZE: u64 = 70_000_000;
+    const MINIMUM_FREE_SPACE: u64 = 30_000_000;
+
+    let plan = cleanup::plan(
+        fs,
+        FS_SIZE,
+        MINIMUM_FREE_SPACE,
+        cleanup::Strategy::SmallestDirectory,
+    )?;
 
-    let currently_used_space = fs.calculate_size(0);
-    let minimum_deletion_size = currently_used_space - MAXIMUM_USED_SPACE;
-
-    fs.nodes
-        .iter()
-        .enumerate()
-        .filter_map(|(i, node)| match node {
-            FsNode::File(_) => None,
-            FsNode::Dir(_) => Some(fs.calculate_size(i)),
-        })
-        .filter(|&dir_size| dir_size >= minimum_deletion_size)
-        .min()
-        .ok_or_else(|| {
-            anyhow!("couldn't find any files meeting the necessary deletion requirements")
-        })
+    Ok(plan.freed)
+}
+
+pub fn part_one(input: &str) -> Result<u64> {
+    solve_part_one(&parse(input)?)
+}
+
+pub fn part_two(input: &str) -> Result<u64> {
+    solve_part_two(&parse(input)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::assert_solves;
 
-    const TEST_INPUT: &str = "$ cd /
+    pub(super) const TEST_INPUT: &str = "$ cd /
 $ ls
 dir a
 14848514 b.txt
@@ -309,6 +589,195 @@ $ ls
         assert_eq!(result, 24_933_642);
     }
 
+    #[test]
+    fn test_fs_lookup() {
+        let fs = Fs::from_transcript(TEST_INPUT).unwrap();
+
+        assert_eq!(fs.root().name(), "/");
+        assert_eq!(fs.total_size(), 48_381_165);
+
+        let Some(FsNode::File(i)) = fs.get("/a/e/i") else {
+            panic!("expected /a/e/i to be a file");
+        };
+        assert_eq!(i.size(), 584);
+
+        let e = fs.get("/a/e/").unwrap();
+        assert!(e.is_dir());
+        assert_eq!(e.size(), 584);
+        assert_eq!(fs.get("/d").unwrap().size(), 24_933_642);
+
+        assert!(fs.get("/a/missing").is_none());
+        assert!(fs.get("/b.txt/foo").is_none());
+        assert!(fs.get("a").is_none());
+    }
+
+    #[test]
+    fn test_fs_children_and_walk() {
+        let fs = Fs::from_transcript(TEST_INPUT).unwrap();
+
+        let root_children = fs.children(fs.root()).map(FsNode::name).collect::<Vec<_>>();
+        assert_eq!(root_children, ["a", "b.txt", "c.dat", "d"]);
+
+        let walked = fs
+            .walk()
+            .map(|entry| (entry.path, entry.depth))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("/", 0),
+            ("/a", 1),
+            ("/a/e", 2),
+            ("/a/e/i", 3),
+            ("/a/f", 2),
+            ("/a/g", 2),
+            ("/a/h.lst", 2),
+            ("/b.txt", 1),
+            ("/c.dat", 1),
+            ("/d", 1),
+            ("/d/d.ext", 2),
+            ("/d/d.log", 2),
+            ("/d/j", 2),
+            ("/d/k", 2),
+        ]
+        .map(|(path, depth)| (path.to_string(), depth));
+        assert_eq!(walked, expected);
+    }
+
+    #[test]
+    fn test_sizes_beyond_u32() {
+        let fs = Fs::from_transcript(
+            "$ cd /
+$ ls
+dir a
+4000000000 b
+$ cd a
+$ ls
+4000000000 c",
+        )
+        .unwrap();
+
+        assert_eq!(fs.get("/a").unwrap().size(), 4_000_000_000);
+        assert_eq!(fs.total_size(), 8_000_000_000);
+    }
+
+    #[test]
+    fn test_size_overflow() {
+        let result = Fs::from_transcript("$ cd /\n$ ls\n18446744073709551615 a\n1 b");
+        assert!(result.is_err());
+
+        // a file that doesn't fit leaves the sizes as they were
+        let mut fs = Fs::new();
+        fs.add_dir("/", "d").unwrap();
+        fs.add_file("/d", "a", u64::MAX - 1).unwrap();
+        assert!(fs.add_file("/d", "b", 2).is_err());
+        assert!(fs.get("/d/b").is_none());
+        assert_eq!(fs.get("/d").unwrap().size(), u64::MAX - 1);
+        assert_eq!(fs.total_size(), u64::MAX - 1);
+        fs.add_file("/", "c", 1).unwrap();
+        assert_eq!(fs.total_size(), u64::MAX);
+    }
+
+    #[test]
+    fn test_multi_segment_cd() {
+        let fs = Fs::from_transcript(
+            "$ cd /
+$ ls
+dir a
+dir x
+$ cd a
+$ ls
+dir b
+$ cd b
+$ ls
+dir c
+$ cd /a/b/c
+$ ls
+10 deep.txt
+$ cd ../../../x
+$ ls
+20 x.txt
+$ cd ./../a/./b
+$ ls
+30 b.txt",
+        )
+        .unwrap();
+
+        assert_eq!(fs.get("/a/b/c/deep.txt").unwrap().size(), 10);
+        assert_eq!(fs.get("/x/x.txt").unwrap().size(), 20);
+        assert_eq!(fs.get("/a/b/b.txt").unwrap().size(), 30);
+        assert_eq!(fs.get("/x/../a").unwrap().size(), 40);
+        assert_eq!(fs.total_size(), 60);
+    }
+
+    #[test]
+    fn test_repeated_ls() {
+        let fs = Fs::from_transcript(
+            "$ cd /
+$ ls
+dir a
+100 b
+$ ls
+dir a
+100 b
+$ cd a
+$ ls
+$ ls
+5 c
+$ cd /
+$ ls
+dir a
+100 b",
+        )
+        .unwrap();
+
+        assert_eq!(fs.walk().count(), 4);
+        assert_eq!(fs.total_size(), 105);
+    }
+
+    #[test]
+    fn test_inconsistent_transcripts() {
+        const INCONSISTENT_TRANSCRIPTS: &[&str] = &[
+            // file changes size between listings
+            "$ cd /\n$ ls\n100 b\n$ ls\n200 b",
+            // file later listed as a directory
+            "$ cd /\n$ ls\n100 b\n$ ls\ndir b",
+            // directory later listed as a file
+            "$ cd /\n$ ls\ndir a\n$ ls\n100 a",
+            // cd into a file
+            "$ cd /\n$ ls\n100 b\n$ cd b",
+            // path passes through a file
+            "$ cd /\n$ ls\n100 b\n$ cd /b/c",
+        ];
+
+        for transcript in INCONSISTENT_TRANSCRIPTS {
+            assert!(Fs::from_transcript(transcript).is_err(), "{transcript:?}");
+        }
+    }
+
+    #[test]
+    fn test_cd_missing_directory() {
+        let result = Fs::from_transcript("$ cd /\n$ cd nowhere");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_model_without_text() {
+        let mut fs = Fs::new();
+        fs.add_dir("/", "a").unwrap();
+        fs.add_dir("/a", "e").unwrap();
+        fs.add_file("/a/e", "i", 584).unwrap();
+        fs.add_file("a", "f", 29_116).unwrap();
+        fs.add_dir("/", "d").unwrap();
+        fs.add_file("/d", "j", 40_000_000).unwrap();
+
+        // freeing up the last 29,700 takes exactly all of `/a`
+        assert_eq!(fs.get("/a").unwrap().size(), 29_700);
+        assert_solves(&fs, solve_part_one, solve_part_two, (584 + 29_700, 29_700));
+
+        assert!(fs.add_file("/a/e/i", "x", 1).is_err());
+        assert!(fs.add_dir("/a/e", "i").is_err());
+        assert!(fs.add_file("/missing", "x", 1).is_err());
+    }
+
     #[test]
     fn solution_part_two() {
         let result = part_two(INPUT).unwrap();
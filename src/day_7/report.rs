@@ -0,0 +1,302 @@
+use crate::json;
+
+use super::{Fs, FsNode, WalkEntry};
+
+/// Renders the filesystem in the same style as the puzzle text, e.g. `- a (dir, size=94853)`.
+pub fn tree(fs: &Fs) -> String {
+    let mut result = String::new();
+    for entry in fs.walk() {
+        let kind = if entry.node.is_dir() { "dir" } else { "file" };
+
+        result.push_str(&format!(
+            "{:indent$}- {} ({}, size={})\n",
+            "",
+            entry.node.name(),
+            kind,
+            entry.node.size(),
+            indent = entry.depth * 2
+        ));
+    }
+
+    result
+}
+
+/// Every directory and its total size, largest first, as `du` would report them.
+pub fn du(fs: &Fs) -> Vec<(String, u64)> {
+    let mut result = fs
+        .walk()
+        .filter(|entry| entry.node.is_dir())
+        .map(|entry| (entry.path, entry.node.size()))
+        .collect::<Vec<_>>();
+
+    result.sort_by(|(a_path, a_size), (b_path, b_size)| {
+        b_size.cmp(a_size).then_with(|| a_path.cmp(b_path))
+    });
+
+    result
+}
+
+/// Renders [`du`] as `du -h` would, one `<size>\t<path>` line per directory.
+pub fn du_human(fs: &Fs) -> String {
+    du(fs)
+        .into_iter()
+        .map(|(path, size)| format!("{}\t{}\n", human_size(size), path))
+        .collect()
+}
+
+/// Formats a size in powers of 1024, rounding up like `du -h` does, e.g. `94853` becomes `93K`.
+pub fn human_size(size: u64) -> String {
+    const UNITS: &[&str] = &["K", "M", "G", "T", "P", "E"];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+
+    let mut scaled = size as f64;
+    let mut unit = UNITS[0];
+    for &next_unit in UNITS {
+        scaled /= 1024.0;
+        unit = next_unit;
+        if scaled < 1024.0 {
+            break;
+        }
+    }
+
+    if scaled < 10.0 {
+        let rounded = (scaled * 10.0).ceil() / 10.0;
+        if rounded < 10.0 {
+            return format!("{rounded:.1}{unit}");
+        }
+    }
+
+    format!("{}{unit}", scaled.ceil())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    Dir,
+    File,
+}
+
+/// Filters for [`find`]. Every `None` field matches everything.
+#[derive(Clone, Debug, Default)]
+pub struct FindQuery {
+    pub kind: Option<NodeKind>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    /// Shell-style glob matched against the node's name, supporting `*` and `?`.
+    pub name: Option<String>,
+    pub min_depth: Option<usize>,
+    pub max_depth: Option<usize>,
+}
+
+impl FindQuery {
+    fn matches(&self, entry: &WalkEntry) -> bool {
+        let kind = match entry.node {
+            FsNode::Dir(_) => NodeKind::Dir,
+            FsNode::File(_) => NodeKind::File,
+        };
+        let size = entry.node.size();
+
+        self.kind.is_none_or(|wanted| wanted == kind)
+            && self.min_size.is_none_or(|min_size| size >= min_size)
+            && self.max_size.is_none_or(|max_size| size <= max_size)
+            && self
+                .min_depth
+                .is_none_or(|min_depth| entry.depth >= min_depth)
+            && self
+                .max_depth
+                .is_none_or(|max_depth| entry.depth <= max_depth)
+            && self
+                .name
+                .as_ref()
+                .is_none_or(|pattern| glob_matches(pattern, entry.node.name()))
+    }
+}
+
+/// Every node matching `query`, in the same depth-first order as [`Fs::walk`].
+pub fn find<'a>(fs: &'a Fs, query: &'a FindQuery) -> impl Iterator<Item = WalkEntry<'a>> + 'a {
+    fs.walk().filter(move |entry| query.matches(entry))
+}
+
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+
+    // classic greedy wildcard matching, backtracking to the most recent `*` on a mismatch
+    let (mut pattern_idx, mut name_idx) = (0, 0);
+    let mut last_star: Option<(usize, usize)> = None;
+    while name_idx < name.len() {
+        match pattern.get(pattern_idx) {
+            Some('*') => {
+                last_star = Some((pattern_idx, name_idx));
+                pattern_idx += 1;
+            }
+            Some(&c) if c == '?' || c == name[name_idx] => {
+                pattern_idx += 1;
+                name_idx += 1;
+            }
+            _ => match last_star {
+                Some((star_pattern_idx, star_name_idx)) => {
+                    pattern_idx = star_pattern_idx + 1;
+                    name_idx = star_name_idx + 1;
+                    last_star = Some((star_pattern_idx, star_name_idx + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[pattern_idx..].iter().all(|&c| c == '*')
+}
+
+/// Exports the whole tree as nested JSON objects with `name`, `type`, `size` and, for
+/// directories, `children`.
+pub fn to_json(fs: &Fs) -> String {
+    let mut result = String::new();
+    let mut open_dirs = 0;
+    let mut needs_comma = false;
+
+    // the walk is depth-first, so a shallower entry means the previous directories are finished
+    for entry in fs.walk() {
+        while open_dirs > entry.depth {
+            result.push_str("]}");
+            open_dirs -= 1;
+            needs_comma = true;
+        }
+
+        if needs_comma {
+            result.push(',');
+        }
+
+        result.push_str("{\"name\":");
+        json::push_string(&mut result, entry.node.name());
+
+        match entry.node {
+            FsNode::Dir(dir) => {
+                result.push_str(&format!(
+                    ",\"type\":\"dir\",\"size\":{},\"children\":[",
+                    dir.size()
+                ));
+                open_dirs += 1;
+                needs_comma = false;
+            }
+            FsNode::File(file) => {
+                result.push_str(&format!(",\"type\":\"file\",\"size\":{}}}", file.size()));
+                needs_comma = true;
+            }
+        }
+    }
+
+    for _ in 0..open_dirs {
+        result.push_str("]}");
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests::TEST_INPUT;
+    use super::*;
+
+    #[test]
+    fn test_tree() {
+        let fs = Fs::from_transcript(TEST_INPUT).unwrap();
+
+        let expected = "- / (dir, size=48381165)
+  - a (dir, size=94853)
+    - e (dir, size=584)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir, size=24933642)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+";
+        assert_eq!(tree(&fs), expected);
+    }
+
+    #[test]
+    fn test_du() {
+        let fs = Fs::from_transcript(TEST_INPUT).unwrap();
+
+        assert_eq!(du_human(&fs), "47M\t/\n24M\t/d\n93K\t/a\n584\t/a/e\n");
+        assert_eq!(human_size(1536), "1.5K");
+        assert_eq!(human_size(10_239), "10K");
+    }
+
+    #[test]
+    fn test_find() {
+        let fs = Fs::from_transcript(TEST_INPUT).unwrap();
+
+        let paths = |query: FindQuery| {
+            find(&fs, &query)
+                .map(|entry| entry.path)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            paths(FindQuery {
+                name: Some("*.*".to_string()),
+                ..Default::default()
+            }),
+            ["/a/h.lst", "/b.txt", "/c.dat", "/d/d.ext", "/d/d.log"]
+        );
+        assert_eq!(
+            paths(FindQuery {
+                kind: Some(NodeKind::Dir),
+                max_size: Some(100_000),
+                ..Default::default()
+            }),
+            ["/a", "/a/e"]
+        );
+        assert_eq!(
+            paths(FindQuery {
+                min_size: Some(5_000_000),
+                min_depth: Some(2),
+                max_depth: Some(2),
+                ..Default::default()
+            }),
+            ["/d/d.ext", "/d/d.log", "/d/k"]
+        );
+        assert_eq!(
+            paths(FindQuery {
+                name: Some("?".to_string()),
+                kind: Some(NodeKind::File),
+                ..Default::default()
+            }),
+            ["/a/e/i", "/a/f", "/a/g", "/d/j", "/d/k"]
+        );
+    }
+
+    #[test]
+    fn test_to_json() {
+        let fs = Fs::from_transcript(
+            "$ cd /
+$ ls
+dir a
+1 \"quoted\"
+$ cd a
+$ ls
+dir empty
+2 b",
+        )
+        .unwrap();
+
+        let expected = concat!(
+            r#"{"name":"/","type":"dir","size":3,"children":["#,
+            r#"{"name":"\"quoted\"","type":"file","size":1},"#,
+            r#"{"name":"a","type":"dir","size":2,"children":["#,
+            r#"{"name":"b","type":"file","size":2},"#,
+            r#"{"name":"empty","type":"dir","size":0,"children":[]}"#,
+            r#"]}]}"#,
+        );
+        assert_eq!(to_json(&fs), expected);
+    }
+}
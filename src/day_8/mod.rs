@@ -1,74 +1,108 @@
 use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+
 use nom::{
-    character::complete::{digit1, newline},
+    bytes::complete::is_a,
+    character::complete::{digit1, newline, space0, space1, u32},
+    combinator::opt,
     multi::separated_list1,
+    sequence::{delimited, terminated},
     IResult,
 };
 
-pub const INPUT: &str = include_str!("./input");
-
-#[derive(Debug)]
-struct ProblemStatement {
-    forest: Vec<Vec<u8>>, // map[y][x]
-}
-
-impl ProblemStatement {
-    fn width(&self) -> usize {
-        self.forest[0].len()
-    }
-
-    fn height(&self) -> usize {
-        self.forest.len()
-    }
-
-    #[allow(dead_code)]
-    fn saturating_up(&self, y: usize) -> usize {
-        y.saturating_sub(1)
-    }
+use crate::grid::Grid;
 
-    #[allow(dead_code)]
-    fn saturating_left(&self, x: usize) -> usize {
-        x.saturating_sub(1)
-    }
+pub mod render;
 
-    fn saturating_down(&self, y: usize) -> usize {
-        usize::min(y + 1, self.height())
-    }
+pub const INPUT: &str = include_str!("./input");
 
-    fn saturating_right(&self, x: usize) -> usize {
-        usize::min(x + 1, self.width())
-    }
+/// How tree heights are written in a forest map. Every format has one row of trees per line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HeightFormat {
+    /// One decimal digit per tree, as in the puzzle input.
+    Digits,
+    /// Whitespace-separated integers, for forests with heights above 9.
+    Integers,
+    /// One character per tree, with heights given by position in the alphabet, so that `"abc"`
+    /// makes `a` a height of 0 and `c` a height of 2. It can't contain whitespace, which would be
+    /// confused with the newlines between rows.
+    Alphabet(String),
 }
 
-fn map_row(input: &str) -> IResult<&str, Vec<u8>> {
+fn digits_row(input: &str) -> IResult<&str, Vec<u32>> {
     let (rest, row) = digit1(input)?;
 
     let result = row
         .chars()
         .map(|c| {
-            // we are guaranteed that these characters will always be valid u8 digits, so parsing should always be possible
-            c.to_digit(10).unwrap() as u8
+            // we are guaranteed that these characters will always be valid digits, so parsing should always be possible
+            c.to_digit(10).unwrap()
         })
         .collect::<Vec<_>>();
 
     Ok((rest, result))
 }
 
-fn forest(input: &str) -> IResult<&str, Vec<Vec<u8>>> {
-    separated_list1(newline, map_row)(input)
+fn integers_row(input: &str) -> IResult<&str, Vec<u32>> {
+    delimited(space0, separated_list1(space1, u32), space0)(input)
 }
 
-fn problem_statement(input: &str) -> IResult<&str, ProblemStatement> {
-    let (rest, forest) = forest(input)?;
+fn alphabet_row<'a>(alphabet: &'a str) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<u32>> {
+    move |input| {
+        let (rest, row) = is_a(alphabet)(input)?;
 
-    let result = ProblemStatement { forest };
+        let result = row
+            .chars()
+            .map(|c| {
+                // `is_a` only matches characters from the alphabet, so they can always be found
+                let height = alphabet.chars().position(|other| other == c).unwrap();
+                height as u32
+            })
+            .collect::<Vec<_>>();
 
-    Ok((rest, result))
+        Ok((rest, result))
+    }
+}
+
+fn forest<'a>(
+    input: &'a str,
+    row: impl FnMut(&'a str) -> IResult<&'a str, Vec<u32>>,
+) -> IResult<&'a str, Vec<Vec<u32>>> {
+    terminated(separated_list1(newline, row), opt(newline))(input)
 }
 
-fn parse_problem_statement(input: &str) -> Result<ProblemStatement> {
-    let (rest, problem_statement) =
-        problem_statement(input).map_err(|err| err.map_input(str::to_string))?;
+/// Reads a forest map written in the puzzle's single-digit format into a grid of tree heights.
+pub fn parse(input: &str) -> Result<Grid<u32>> {
+    parse_with_format(input, &HeightFormat::Digits)
+}
+
+pub fn parse_with_format(input: &str, format: &HeightFormat) -> Result<Grid<u32>> {
+    if input.trim().is_empty() {
+        return Err(anyhow!(
+            "expected a forest with at least one tree, but got no input"
+        ));
+    }
+
+    let (rest, rows) = match format {
+        HeightFormat::Digits => forest(input, digits_row),
+        HeightFormat::Integers => forest(input, integers_row),
+        HeightFormat::Alphabet(alphabet) => {
+            let mut seen = HashSet::new();
+            if alphabet.is_empty()
+                || !alphabet
+                    .chars()
+                    .all(|c| !c.is_whitespace() && seen.insert(c))
+            {
+                return Err(anyhow!(
+                    "expected an alphabet of distinct non-whitespace characters, but got {:?}",
+                    alphabet
+                ));
+            }
+
+            forest(input, alphabet_row(alphabet))
+        }
+    }
+    .map_err(|err| err.map_input(str::to_string))?;
 
     if !rest.is_empty() {
         return Err(anyhow!(
@@ -77,142 +111,241 @@ fn parse_problem_statement(input: &str) -> Result<ProblemStatement> {
         ));
     }
 
-    Ok(problem_statement)
+    Grid::from_rows(rows)
 }
 
-pub fn part_one(input: &str) -> Result<u32> {
-    #![allow(clippy::needless_range_loop)]
-
-    let problem_statement = parse_problem_statement(input)?;
-    let mut seen_map = vec![vec![false; problem_statement.width()]; problem_statement.height()];
-
-    // left to right
-    for y in 0..problem_statement.height() {
-        let mut tallest = problem_statement.forest[y][0];
-        seen_map[y][0] = true;
-        for x in 0..problem_statement.width() {
-            let current_height = problem_statement.forest[y][x];
-            if current_height > tallest {
-                seen_map[y][x] = true;
-                tallest = current_height;
+#[derive(Clone, Copy, Debug, Default)]
+struct Sightline {
+    // no tree between this one and the edge is at least as tall
+    visible: bool,
+    // number of trees seen before being blocked by one at least as tall, or reaching the edge
+    viewing_distance: u32,
+}
+
+// For every tree, its sightline looking up, down, left and right. Every row and column is swept
+// once per direction while keeping a stack of the trees that could still block the view of a
+// later one, which is non-increasing in height. Shorter trees are popped since the current
+// one hides them from everything after it, so each tree is pushed and popped at most once per
+// sweep, making the whole thing O(w * h).
+fn sightlines(forest: &Grid<u32>) -> Grid<[Sightline; 4]> {
+    const UP: usize = 0;
+    const DOWN: usize = 1;
+    const LEFT: usize = 2;
+    const RIGHT: usize = 3;
+
+    let mut result = Grid::new(forest.width(), forest.height(), [Sightline::default(); 4]);
+
+    let mut sweep = |direction_idx: usize, positions: &mut dyn Iterator<Item = (usize, usize)>| {
+        let mut stack: Vec<(u32, u32)> = Vec::new(); // (step, height)
+        for (step, position) in (0..).zip(positions) {
+            let current_height = forest[position];
+            while stack
+                .last()
+                .is_some_and(|&(_, height)| height < current_height)
+            {
+                stack.pop();
             }
+
+            result[position][direction_idx] = match stack.last() {
+                Some(&(blocker_step, _)) => Sightline {
+                    visible: false,
+                    viewing_distance: step - blocker_step,
+                },
+                None => Sightline {
+                    visible: true,
+                    viewing_distance: step,
+                },
+            };
+
+            stack.push((step, current_height));
         }
+    };
+
+    // the trees that can block the view in a direction are the ones swept over before it
+    for x in 0..forest.width() {
+        sweep(UP, &mut (0..forest.height()).map(|y| (x, y)));
+        sweep(DOWN, &mut (0..forest.height()).rev().map(|y| (x, y)));
     }
 
-    // right to left
-    for y in 0..problem_statement.height() {
-        let mut tallest = problem_statement.forest[y][problem_statement.width() - 1];
-        seen_map[y][problem_statement.width() - 1] = true;
-        for x in (0..problem_statement.width()).rev() {
-            let current_height = problem_statement.forest[y][x];
-            if current_height > tallest {
-                seen_map[y][x] = true;
-                tallest = current_height;
-            }
-        }
+    for y in 0..forest.height() {
+        sweep(LEFT, &mut (0..forest.width()).map(|x| (x, y)));
+        sweep(RIGHT, &mut (0..forest.width()).rev().map(|x| (x, y)));
     }
 
-    // top to bottom
-    for x in 0..problem_statement.width() {
-        let mut tallest = problem_statement.forest[0][x];
-        seen_map[0][x] = true;
-        for y in 0..problem_statement.height() {
-            let current_height = problem_statement.forest[y][x];
-            if current_height > tallest {
-                seen_map[y][x] = true;
-                tallest = current_height;
-            }
-        }
+    result
+}
+
+/// Which edges of the forest a tree can be seen from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Visibility {
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+}
+
+impl Visibility {
+    pub fn any(&self) -> bool {
+        self.up || self.down || self.left || self.right
     }
+}
 
-    // bottom to top
-    for x in 0..problem_statement.width() {
-        let mut tallest = problem_statement.forest[problem_statement.height() - 1][x];
-        seen_map[problem_statement.height() - 1][x] = true;
-        for y in (0..problem_statement.height()).rev() {
-            let current_height = problem_statement.forest[y][x];
-            if current_height > tallest {
-                seen_map[y][x] = true;
-                tallest = current_height;
-            }
+/// Per-tree results for a whole forest, indexed the same way as the input map.
+#[derive(Clone, Debug)]
+pub struct ForestAnalysis {
+    visibility: Grid<Visibility>,
+    scenic_scores: Grid<u64>,
+}
+
+impl ForestAnalysis {
+    /// Analyzes a grid of tree heights, which can have any size.
+    pub fn new(forest: &Grid<u32>) -> Self {
+        let sightlines = sightlines(forest);
+
+        let visibility = sightlines.map(|[up, down, left, right]| Visibility {
+            up: up.visible,
+            down: down.visible,
+            left: left.visible,
+            right: right.visible,
+        });
+
+        let scenic_scores = sightlines.map(|sightlines| {
+            sightlines
+                .iter()
+                .map(|sightline| u64::from(sightline.viewing_distance))
+                .product()
+        });
+
+        Self {
+            visibility,
+            scenic_scores,
         }
     }
 
-    // count number of boolean "true" values in this 2d array.
-    let num_seen = seen_map
-        .into_iter()
-        .map(|row| row.into_iter().filter(|val| *val).count())
-        .sum::<usize>()
-        .try_into()?;
+    pub fn visibility(&self) -> &Grid<Visibility> {
+        &self.visibility
+    }
 
-    Ok(num_seen)
-}
+    pub fn scenic_scores(&self) -> &Grid<u64> {
+        &self.scenic_scores
+    }
 
-pub fn part_two(input: &str) -> Result<u32> {
-    let problem_statement = parse_problem_statement(input)?;
-    let mut best_score = 0;
-    for y in 0..problem_statement.height() {
-        for x in 0..problem_statement.width() {
-            // tree to top
-            let mut up_score = 0;
-            let current_height = problem_statement.forest[y][x];
-            for tmp_y in (0..y).rev() {
-                up_score += 1;
-
-                if current_height <= problem_statement.forest[tmp_y][x] {
-                    break;
-                }
-            }
+    pub fn num_visible(&self) -> usize {
+        self.visibility
+            .iter()
+            .filter(|(_, visibility)| visibility.any())
+            .count()
+    }
 
-            // tree to bottom
-            let mut down_score = 0;
-            let current_height = problem_statement.forest[y][x];
-            for tmp_y in problem_statement.saturating_down(y)..problem_statement.height() {
-                down_score += 1;
+    pub fn best_scenic_score(&self) -> u64 {
+        self.scenic_scores
+            .iter()
+            .map(|(_, &score)| score)
+            .max()
+            .unwrap_or(0)
+    }
 
-                if current_height <= problem_statement.forest[tmp_y][x] {
-                    break;
-                }
-            }
+    /// Positions of every tree sharing the best scenic score, in row-major order.
+    pub fn best_trees(&self) -> Vec<(usize, usize)> {
+        let best_score = self.best_scenic_score();
 
-            // tree to left
-            let mut left_score = 0;
-            let current_height = problem_statement.forest[y][x];
-            for tmp_x in (0..x).rev() {
-                left_score += 1;
+        self.scenic_scores
+            .iter()
+            .filter(|(_, &score)| score == best_score)
+            .map(|(position, _)| position)
+            .collect()
+    }
+}
 
-                if current_height <= problem_statement.forest[y][tmp_x] {
-                    break;
-                }
-            }
+/// Analyzes a forest map written in the puzzle's single-digit format.
+pub fn analyze(input: &str) -> Result<ForestAnalysis> {
+    analyze_with_format(input, &HeightFormat::Digits)
+}
 
-            // tree to right
-            let mut right_score = 0;
-            let current_height = problem_statement.forest[y][x];
-            for tmp_x in problem_statement.saturating_right(x)..problem_statement.width() {
-                right_score += 1;
+pub fn analyze_with_format(input: &str, format: &HeightFormat) -> Result<ForestAnalysis> {
+    let forest = parse_with_format(input, format)?;
 
-                if current_height <= problem_statement.forest[y][tmp_x] {
-                    break;
-                }
-            }
+    Ok(ForestAnalysis::new(&forest))
+}
 
-            let this_score = left_score * right_score * up_score * down_score;
+pub fn solve_part_one(forest: &Grid<u32>) -> Result<u32> {
+    let num_visible = ForestAnalysis::new(forest).num_visible().try_into()?;
 
-            if this_score > best_score {
-                best_score = this_score;
-            }
-        }
-    }
+    Ok(num_visible)
+}
+
+pub fn solve_part_two(forest: &Grid<u32>) -> Result<u32> {
+    let best_score = ForestAnalysis::new(forest).best_scenic_score().try_into()?;
 
     Ok(best_score)
 }
 
+pub fn part_one(input: &str) -> Result<u32> {
+    solve_part_one(&parse(input)?)
+}
+
+pub fn part_two(input: &str) -> Result<u32> {
+    solve_part_two(&parse(input)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::grid::Direction;
+
+    // checks every tree by walking rays out to the edge, which is O(n * (w + h))
+    fn count_visible_brute_force(forest: &Grid<u32>) -> usize {
+        forest
+            .iter()
+            .filter(|&(position, &current_height)| {
+                Direction::CARDINAL.into_iter().any(|direction| {
+                    forest
+                        .ray(position, direction)
+                        .all(|(_, &other_height)| other_height < current_height)
+                })
+            })
+            .count()
+    }
+
+    fn best_scenic_score_brute_force(forest: &Grid<u32>) -> u64 {
+        forest
+            .iter()
+            .map(|(position, &current_height)| {
+                Direction::CARDINAL
+                    .into_iter()
+                    .map(|direction| {
+                        let mut score = 0;
+                        for (_, &other_height) in forest.ray(position, direction) {
+                            score += 1;
+
+                            if current_height <= other_height {
+                                break;
+                            }
+                        }
+
+                        score
+                    })
+                    .product::<u64>()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    // xorshift, so that generated forests are the same on every run
+    fn generate_forest(width: usize, height: usize, max_height: u32, mut seed: u64) -> Grid<u32> {
+        let cells = (0..width * height)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                (seed % (u64::from(max_height) + 1)) as u32
+            })
+            .collect();
+
+        Grid::from_vec(width, height, cells).unwrap()
+    }
 
-    const TEST_INPUT: &str = "30373
+    pub(super) const TEST_INPUT: &str = "30373
 25512
 65332
 33549
@@ -236,6 +369,114 @@ mod tests {
         assert_eq!(result, 8);
     }
 
+    #[test]
+    fn test_analysis() {
+        let analysis = analyze(TEST_INPUT).unwrap();
+
+        // the top-left 5 in the second row can only be seen from the left and the top
+        let expected = Visibility {
+            up: true,
+            down: false,
+            left: true,
+            right: false,
+        };
+        assert_eq!(analysis.visibility()[(1, 1)], expected);
+        assert!(!analysis.visibility()[(3, 1)].any());
+
+        assert_eq!(analysis.scenic_scores()[(2, 1)], 4);
+        assert_eq!(analysis.scenic_scores()[(2, 3)], 8);
+        assert_eq!(analysis.scenic_scores()[(0, 0)], 0);
+        assert_eq!(analysis.best_trees(), [(2, 3)]);
+    }
+
+    #[test]
+    fn test_height_formats() {
+        // the example forest with every height multiplied by 10
+        const INTEGERS_INPUT: &str = "30 0 30 70 30
+20 50 50 10 20
+60 50 30 30 20
+30 30 50 40 90
+30 50 30 90 0
+";
+
+        let digits = analyze(TEST_INPUT).unwrap();
+        let integers = analyze_with_format(INTEGERS_INPUT, &HeightFormat::Integers).unwrap();
+        assert_eq!(integers.visibility(), digits.visibility());
+        assert_eq!(integers.scenic_scores(), digits.scenic_scores());
+
+        let towering =
+            analyze_with_format("1 1 1\n1 10 1\n1 1 1", &HeightFormat::Integers).unwrap();
+        assert_eq!(towering.num_visible(), 9);
+        assert_eq!(towering.best_trees(), [(1, 1)]);
+
+        let alphabet = HeightFormat::Alphabet("abcdefghijklmnopqrstuvwxyz".to_string());
+        let letters = analyze_with_format(
+            "dadhd
+cffbc
+gfddc
+ddfej
+dfdja",
+            &alphabet,
+        )
+        .unwrap();
+        assert_eq!(letters.visibility(), digits.visibility());
+        assert_eq!(letters.scenic_scores(), digits.scenic_scores());
+    }
+
+    #[test]
+    fn test_invalid_forests() {
+        const INVALID_FORESTS: &[(&str, HeightFormat)] = &[
+            ("", HeightFormat::Digits),
+            ("\n", HeightFormat::Integers),
+            ("123\n45\n678", HeightFormat::Digits),
+            ("1 2 3\n4 5\n6 7 8", HeightFormat::Integers),
+            ("12a", HeightFormat::Digits),
+            ("1 2 -3", HeightFormat::Integers),
+        ];
+
+        for (input, format) in INVALID_FORESTS {
+            assert!(analyze_with_format(input, format).is_err(), "{input:?}");
+        }
+
+        for alphabet in ["", "abca", "ab c", "ab\nc"] {
+            let alphabet = HeightFormat::Alphabet(alphabet.to_string());
+            assert!(
+                analyze_with_format("abc", &alphabet).is_err(),
+                "{alphabet:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        const TEST_CASES: &[(usize, usize, u32, u64)] = &[
+            (1, 1, 9, 1),
+            (1, 7, 9, 2),
+            (7, 1, 9, 3),
+            (20, 30, 0, 4),
+            (50, 50, 3, 5),
+            (100, 100, 8_848, 7),
+            (300, 200, u32::MAX, 8),
+            (1_000, 1_000, 9, 6),
+        ];
+
+        for &(width, height, max_height, seed) in TEST_CASES {
+            let forest = generate_forest(width, height, max_height, seed);
+
+            let analysis = ForestAnalysis::new(&forest);
+            assert_eq!(
+                analysis.num_visible(),
+                count_visible_brute_force(&forest),
+                "{width}x{height}"
+            );
+            assert_eq!(
+                analysis.best_scenic_score(),
+                best_scenic_score_brute_force(&forest),
+                "{width}x{height}"
+            );
+        }
+    }
+
     #[test]
     fn solution_part_two() {
         let result = part_two(INPUT).unwrap();
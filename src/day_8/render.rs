@@ -0,0 +1,103 @@
+use super::ForestAnalysis;
+
+/// One line per row, with `#` for trees visible from outside the forest and `.` for hidden ones.
+pub fn visibility_ascii(analysis: &ForestAnalysis) -> String {
+    let mut result = String::new();
+    for row in analysis.visibility().rows() {
+        result.extend(
+            row.iter()
+                .map(|visibility| if visibility.any() { '#' } else { '.' }),
+        );
+        result.push('\n');
+    }
+
+    result
+}
+
+// a handful of trees have scores orders of magnitude above the rest, so shades are log-scaled
+fn shade(score: u64, best_score: u64) -> f64 {
+    if best_score == 0 {
+        return 0.0;
+    }
+
+    (score as f64).ln_1p() / (best_score as f64).ln_1p()
+}
+
+/// One line per row, shading every tree's scenic score from ` ` (zero) to `@` (the best score).
+pub fn scenic_ascii(analysis: &ForestAnalysis) -> String {
+    const RAMP: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+    let best_score = analysis.best_scenic_score();
+
+    let mut result = String::new();
+    for row in analysis.scenic_scores().rows() {
+        result.extend(row.iter().map(|&score| {
+            let level = (shade(score, best_score) * (RAMP.len() - 1) as f64).round() as usize;
+            RAMP[level]
+        }));
+        result.push('\n');
+    }
+
+    result
+}
+
+/// The scenic scores as a plain (ASCII) PGM image, from black for zero to white for the best score.
+pub fn scenic_pgm(analysis: &ForestAnalysis) -> String {
+    const MAX_VALUE: u8 = u8::MAX;
+
+    let scores = analysis.scenic_scores();
+    let best_score = analysis.best_scenic_score();
+
+    let mut result = format!("P2\n{} {}\n{MAX_VALUE}\n", scores.width(), scores.height());
+
+    for row in scores.rows() {
+        let line = row
+            .iter()
+            .map(|&score| {
+                let value = (shade(score, best_score) * f64::from(MAX_VALUE)).round() as u8;
+                value.to_string()
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        result.push_str(&line);
+        result.push('\n');
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{analyze, tests::TEST_INPUT};
+    use super::*;
+
+    #[test]
+    fn test_visibility_ascii() {
+        let analysis = analyze(TEST_INPUT).unwrap();
+
+        let expected = "#####
+###.#
+##.##
+#.#.#
+#####
+";
+        assert_eq!(visibility_ascii(&analysis), expected);
+    }
+
+    #[test]
+    fn test_scenic_renders() {
+        let analysis = analyze(TEST_INPUT).unwrap();
+
+        let ascii = scenic_ascii(&analysis);
+        assert_eq!(ascii.lines().count(), 5);
+        assert_eq!(ascii.lines().nth(3).unwrap().chars().nth(2), Some('@'));
+        assert!(ascii.lines().all(|line| line.starts_with(' ')));
+
+        let pgm = scenic_pgm(&analysis);
+        let mut lines = pgm.lines();
+        assert_eq!(lines.next(), Some("P2"));
+        assert_eq!(lines.next(), Some("5 5"));
+        assert_eq!(lines.next(), Some("255"));
+        assert_eq!(lines.nth(3), Some("0 80 255 161 0"));
+    }
+}
@@ -1,132 +1,295 @@
-use std::collections::HashSet;
-
 use anyhow::{anyhow, Result};
 use nom::{
+    branch::alt,
     bytes::complete::tag,
-    character::complete::u8,
-    character::complete::{newline, one_of},
+    character::complete::u32,
+    character::complete::{char, newline},
+    combinator::value,
     multi::separated_list1,
     sequence::separated_pair,
     IResult,
 };
 
+use visited::VisitedCells;
+
+pub mod render;
+pub mod visited;
+
 pub const INPUT: &str = include_str!("./input");
 
-#[derive(Debug)]
-enum Direction {
+/// A position in `D` dimensions. The first two axes are `x` and `y`, with `y` growing upwards, and
+/// the third is `z`, growing forwards.
+pub type Position<const D: usize> = [i32; D];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
     Right,
     Up,
     Left,
     Down,
+    UpRight,
+    UpLeft,
+    DownRight,
+    DownLeft,
+    Forward,
+    Backward,
 }
 
-#[derive(Debug)]
-struct Move {
-    direction: Direction,
-    amount: u8,
+impl Direction {
+    /// The `[dx, dy, dz]` of a single step in this direction, with `y` growing upwards.
+    pub fn offset(self) -> [i32; 3] {
+        match self {
+            Direction::Right => [1, 0, 0],
+            Direction::Up => [0, 1, 0],
+            Direction::Left => [-1, 0, 0],
+            Direction::Down => [0, -1, 0],
+            Direction::UpRight => [1, 1, 0],
+            Direction::UpLeft => [-1, 1, 0],
+            Direction::DownRight => [1, -1, 0],
+            Direction::DownLeft => [-1, -1, 0],
+            Direction::Forward => [0, 0, 1],
+            Direction::Backward => [0, 0, -1],
+        }
+    }
+
+    /// The offset of a single step in a world with `D` dimensions, if it has enough of them.
+    pub fn offset_in<const D: usize>(self) -> Result<Position<D>> {
+        let offset = self.offset();
+
+        let mut result = [0; D];
+        for (axis, delta) in offset.into_iter().enumerate() {
+            match result.get_mut(axis) {
+                Some(component) => *component = delta,
+                None if delta == 0 => {}
+                None => {
+                    return Err(anyhow!(
+                        "moving {:?} needs at least {} dimensions, but the rope only has {}",
+                        self,
+                        axis + 1,
+                        D
+                    ))
+                }
+            }
+        }
+
+        Ok(result)
+    }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub direction: Direction,
+    pub amount: u32,
+}
+
+/// A rope of knots moving through `D` dimensions, which is the puzzle's plane by default.
 #[derive(Debug)]
-struct WorldState {
-    nodes: Vec<(i32, i32)>,
-    seen_tail: HashSet<(i32, i32)>,
+pub struct WorldState<const D: usize = 2> {
+    nodes: Vec<Position<D>>,
+    seen: Vec<VisitedCells<D>>,
+    // `None` when paths aren't being recorded
+    paths: Option<Vec<Vec<Position<D>>>>,
 }
 
-impl WorldState {
-    fn new(num_nodes: usize) -> Result<Self> {
+impl<const D: usize> WorldState<D> {
+    pub fn new(num_nodes: usize) -> Result<Self> {
+        let mut result = Self::without_paths(num_nodes)?;
+        result.paths = Some(vec![vec![[0; D]]; num_nodes]);
+
+        Ok(result)
+    }
+
+    /// Like [`WorldState::new`], but doesn't keep every knot's path, which grows with every step
+    /// and is only needed to replay the simulation.
+    pub fn without_paths(num_nodes: usize) -> Result<Self> {
         if num_nodes == 0 {
             return Err(anyhow!("cannot have a world state with no nodes"));
         }
 
         let result = Self {
-            nodes: vec![(0, 0); num_nodes],
-            seen_tail: HashSet::new(),
+            nodes: vec![[0; D]; num_nodes],
+            seen: vec![VisitedCells::from_iter([[0; D]]); num_nodes],
+            paths: None,
         };
 
         Ok(result)
     }
 
-    fn head_up(&mut self) {
-        self.nodes[0].1 += 1;
+    /// Moves the head by `delta`, which may be diagonal but must be at most one cell along each
+    /// axis, and lets every following knot catch up.
+    pub fn step(&mut self, delta: Position<D>) -> Result<()> {
+        self.advance(delta).map(|_| ())
+    }
+
+    // returns whether every knot moved by exactly `delta`
+    fn advance(&mut self, delta: Position<D>) -> Result<bool> {
+        if delta.iter().any(|component| component.abs() > 1) {
+            return Err(anyhow!(
+                "head can only move one cell along each axis at a time, but tried to move by {:?}",
+                delta
+            ));
+        }
 
+        self.nodes[0] = Self::offset_by(self.nodes[0], delta, 1)?;
+
+        let mut in_lockstep = true;
         for (head_idx, tail_idx) in (0..).zip(1..self.nodes.len()) {
-            self.nodes[tail_idx] = Self::catch_up(self.nodes[head_idx], self.nodes[tail_idx]);
+            let tail = self.nodes[tail_idx];
+            self.nodes[tail_idx] = Self::catch_up(self.nodes[head_idx], tail);
+
+            in_lockstep &= self.nodes[tail_idx]
+                .iter()
+                .zip(tail)
+                .zip(delta)
+                .all(|((&new, old), delta)| new - old == delta);
         }
 
-        // empty nodes is an invalid world state
-        self.seen_tail.insert(*self.nodes.last().unwrap());
+        for idx in 0..self.nodes.len() {
+            self.visit(idx, self.nodes[idx]);
+        }
+
+        Ok(in_lockstep)
     }
 
-    fn head_down(&mut self) {
-        self.nodes[0].1 -= 1;
+    fn visit(&mut self, idx: usize, node: Position<D>) {
+        self.seen[idx].insert(node);
 
-        for (head_idx, tail_idx) in (0..).zip(1..self.nodes.len()) {
-            self.nodes[tail_idx] = Self::catch_up(self.nodes[head_idx], self.nodes[tail_idx]);
+        // only record positions that actually changed, so paths don't fill up with a resting knot
+        if let Some(paths) = &mut self.paths {
+            if paths[idx].last() != Some(&node) {
+                paths[idx].push(node);
+            }
         }
+    }
 
-        // empty nodes is an invalid world state
-        self.seen_tail.insert(*self.nodes.last().unwrap());
+    fn offset_by(position: Position<D>, delta: Position<D>, times: u32) -> Result<Position<D>> {
+        let mut result = position;
+        for (component, delta) in result.iter_mut().zip(delta) {
+            *component = i64::from(delta)
+                .checked_mul(i64::from(times))
+                .and_then(|offset| offset.checked_add(i64::from(*component)))
+                .and_then(|component| i32::try_from(component).ok())
+                .ok_or_else(|| {
+                    anyhow!(
+                        "moving {:?} by {:?} {} times leaves the representable world",
+                        position,
+                        delta,
+                        times
+                    )
+                })?;
+        }
+
+        Ok(result)
     }
 
-    fn head_left(&mut self) {
-        self.nodes[0].0 -= 1;
+    pub fn apply(&mut self, this_move: Move) -> Result<()> {
+        let delta = this_move.direction.offset_in()?;
 
-        for (head_idx, tail_idx) in (0..).zip(1..self.nodes.len()) {
-            self.nodes[tail_idx] = Self::catch_up(self.nodes[head_idx], self.nodes[tail_idx]);
+        for steps_left in (0..this_move.amount).rev() {
+            // once every knot moves exactly like the head, the rope is pulled straight behind it
+            // and the rest of the move just slides the whole rope along
+            if self.advance(delta)? {
+                return self.slide(delta, steps_left);
+            }
         }
 
-        // empty nodes is an invalid world state
-        self.seen_tail.insert(*self.nodes.last().unwrap());
+        Ok(())
     }
 
-    fn head_right(&mut self) {
-        self.nodes[0].0 += 1;
+    fn slide(&mut self, delta: Position<D>, steps: u32) -> Result<()> {
+        // check the far end of the move first, so that a failure leaves the rope untouched
+        for &node in &self.nodes {
+            Self::offset_by(node, delta, steps)?;
+        }
 
-        for (head_idx, tail_idx) in (0..).zip(1..self.nodes.len()) {
-            self.nodes[tail_idx] = Self::catch_up(self.nodes[head_idx], self.nodes[tail_idx]);
+        for idx in 0..self.nodes.len() {
+            // can't overflow, as every cell on the way lies between the start and the checked end
+            let mut node = self.nodes[idx];
+            let seen = &mut self.seen[idx];
+            let mut path = self.paths.as_mut().map(|paths| &mut paths[idx]);
+            for _ in 0..steps {
+                for (component, delta) in node.iter_mut().zip(delta) {
+                    *component += delta;
+                }
+
+                seen.insert(node);
+                if let Some(path) = &mut path {
+                    path.push(node);
+                }
+            }
+
+            self.nodes[idx] = node;
         }
 
-        // empty nodes is an invalid world state
-        self.seen_tail.insert(*self.nodes.last().unwrap());
+        Ok(())
     }
 
-    fn catch_up(head: (i32, i32), tail: (i32, i32)) -> (i32, i32) {
-        let diff_x = head.0 - tail.0;
-        let diff_y = head.1 - tail.1;
-
-        let mut result_x = tail.0;
-        let mut result_y = tail.1;
+    // knots touch while their Chebyshev distance is at most one; once it isn't, the tail moves one
+    // cell towards the head along every axis they differ in
+    fn catch_up(head: Position<D>, tail: Position<D>) -> Position<D> {
+        let mut diff = [0; D];
+        for ((diff, head), tail) in diff.iter_mut().zip(head).zip(tail) {
+            *diff = head - tail;
+        }
 
-        // if moved far enough away, have tail catch up to head
-        if diff_x.abs() > 1 || diff_y.abs() > 1 {
-            result_x += diff_x.signum();
-            result_y += diff_y.signum();
+        let mut result = tail;
+        if diff.iter().any(|component| component.abs() > 1) {
+            for (component, diff) in result.iter_mut().zip(diff) {
+                *component += diff.signum();
+            }
         }
 
-        (result_x, result_y)
+        result
+    }
+
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Current positions of every knot, head first.
+    pub fn nodes(&self) -> &[Position<D>] {
+        &self.nodes
+    }
+
+    /// Number of distinct cells knot `idx` (with the head at 0) has visited, including the start.
+    pub fn num_visited(&self, idx: usize) -> Option<usize> {
+        self.seen.get(idx).map(VisitedCells::len)
+    }
+
+    pub fn visited(&self, idx: usize) -> Option<&VisitedCells<D>> {
+        self.seen.get(idx)
+    }
+
+    /// Every position knot `idx` has moved through, in order, starting from the origin. Always
+    /// `None` for a world state created [`without_paths`](WorldState::without_paths).
+    pub fn path(&self, idx: usize) -> Option<&[Position<D>]> {
+        self.paths.as_ref()?.get(idx).map(Vec::as_slice)
     }
 
     fn num_seen_tail_positions(&self) -> usize {
-        self.seen_tail.len()
+        // empty nodes is an invalid world state
+        self.seen.last().unwrap().len()
     }
 }
 
 fn direction(input: &str) -> IResult<&str, Direction> {
-    let (rest, dir_char) = one_of("RULD")(input)?;
-
-    let result = match dir_char {
-        'R' => Direction::Right,
-        'U' => Direction::Up,
-        'L' => Direction::Left,
-        'D' => Direction::Down,
-        _ => unreachable!("unexpected direction character {dir_char:?}"),
-    };
-
-    Ok((rest, result))
+    // diagonals first, so that e.g. `UL` isn't taken as `U` followed by garbage
+    alt((
+        value(Direction::UpRight, tag("UR")),
+        value(Direction::UpLeft, tag("UL")),
+        value(Direction::DownRight, tag("DR")),
+        value(Direction::DownLeft, tag("DL")),
+        value(Direction::Right, char('R')),
+        value(Direction::Up, char('U')),
+        value(Direction::Left, char('L')),
+        value(Direction::Down, char('D')),
+        value(Direction::Forward, char('F')),
+        value(Direction::Backward, char('B')),
+    ))(input)
 }
 
 fn parse_move(input: &str) -> IResult<&str, Move> {
-    let (rest, (direction, amount)) = separated_pair(direction, tag(" "), u8)(input)?;
+    let (rest, (direction, amount)) = separated_pair(direction, tag(" "), u32)(input)?;
 
     let result = Move { direction, amount };
 
@@ -137,7 +300,7 @@ fn problem_statement(input: &str) -> IResult<&str, Vec<Move>> {
     separated_list1(newline, parse_move)(input)
 }
 
-fn parse_problem_statement(input: &str) -> Result<Vec<Move>> {
+pub fn parse(input: &str) -> Result<Vec<Move>> {
     let (rest, problem_statement) =
         problem_statement(input).map_err(|err| err.map_input(str::to_string))?;
 
@@ -151,21 +314,23 @@ fn parse_problem_statement(input: &str) -> Result<Vec<Move>> {
     Ok(problem_statement)
 }
 
-pub fn part_one(input: &str) -> Result<u32> {
-    const NUM_NODES: usize = 2;
-
-    let moves = parse_problem_statement(input)?;
-    let mut world_state = WorldState::new(NUM_NODES)?;
+/// Simulates a rope of `num_nodes` knots in `D` dimensions following every move in `input`.
+pub fn simulate<const D: usize>(input: &str, num_nodes: usize) -> Result<WorldState<D>> {
+    let moves = parse(input)?;
+    let mut world_state = WorldState::new(num_nodes)?;
 
     for this_move in moves {
-        let move_function = match this_move.direction {
-            Direction::Down => WorldState::head_down,
-            Direction::Left => WorldState::head_left,
-            Direction::Right => WorldState::head_right,
-            Direction::Up => WorldState::head_up,
-        };
+        world_state.apply(this_move)?;
+    }
+
+    Ok(world_state)
+}
 
-        (0..this_move.amount).for_each(|_| move_function(&mut world_state));
+fn num_tail_positions(moves: &[Move], num_nodes: usize) -> Result<u32> {
+    let mut world_state = WorldState::<2>::without_paths(num_nodes)?;
+
+    for &this_move in moves {
+        world_state.apply(this_move)?;
     }
 
     let result = world_state.num_seen_tail_positions().try_into()?;
@@ -173,33 +338,31 @@ pub fn part_one(input: &str) -> Result<u32> {
     Ok(result)
 }
 
-pub fn part_two(input: &str) -> Result<u32> {
-    const NUM_NODES: usize = 10;
+pub fn solve_part_one(moves: &[Move]) -> Result<u32> {
+    const NUM_NODES: usize = 2;
 
-    let moves = parse_problem_statement(input)?;
-    let mut world_state = WorldState::new(NUM_NODES)?;
+    num_tail_positions(moves, NUM_NODES)
+}
 
-    for this_move in moves {
-        let move_function = match this_move.direction {
-            Direction::Down => WorldState::head_down,
-            Direction::Left => WorldState::head_left,
-            Direction::Right => WorldState::head_right,
-            Direction::Up => WorldState::head_up,
-        };
+pub fn solve_part_two(moves: &[Move]) -> Result<u32> {
+    const NUM_NODES: usize = 10;
 
-        (0..this_move.amount).for_each(|_| move_function(&mut world_state));
-    }
+    num_tail_positions(moves, NUM_NODES)
+}
 
-    let result = world_state.num_seen_tail_positions().try_into()?;
+pub fn part_one(input: &str) -> Result<u32> {
+    solve_part_one(&parse(input)?)
+}
 
-    Ok(result)
+pub fn part_two(input: &str) -> Result<u32> {
+    solve_part_two(&parse(input)?)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const TEST_PART_1_INPUT: &str = "R 4
+    pub(super) const TEST_PART_1_INPUT: &str = "R 4
 U 4
 L 3
 D 1
@@ -241,6 +404,111 @@ U 20";
         assert_eq!(result, 36);
     }
 
+    #[test]
+    fn test_every_knot_tracked() {
+        let world_state: WorldState = simulate(TEST_PART_2_INPUT, 10).unwrap();
+
+        assert_eq!(world_state.num_visited(9), Some(36));
+        assert_eq!(world_state.num_visited(10), None);
+
+        // the head visits every cell it passes through, and each knot can only cover less ground
+        let visited = (0..10)
+            .map(|idx| world_state.num_visited(idx).unwrap())
+            .collect::<Vec<_>>();
+        assert!(visited.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        // the head's path is just the moves laid end to end
+        let head_path = world_state.path(0).unwrap();
+        assert_eq!(head_path.len(), 1 + 5 + 8 + 8 + 3 + 17 + 10 + 25 + 20);
+        assert_eq!(head_path.last(), Some(&[-11, 15]));
+        assert_eq!(world_state.nodes()[0], [-11, 15]);
+
+        // every knot moves at most one cell along each axis at a time
+        for idx in 0..10 {
+            let path = world_state.path(idx).unwrap();
+            assert!(path
+                .windows(2)
+                .all(|pair| (pair[0][0] - pair[1][0]).abs() <= 1
+                    && (pair[0][1] - pair[1][1]).abs() <= 1));
+        }
+    }
+
+    #[test]
+    fn test_diagonal_moves() {
+        let world_state: WorldState = simulate("UR 3\nDL 1\nUL 2\nDR 2", 2).unwrap();
+
+        assert_eq!(world_state.nodes(), [[2, 2], [1, 3]]);
+        assert_eq!(
+            world_state.path(1).unwrap(),
+            [[0, 0], [1, 1], [2, 2], [1, 3]]
+        );
+
+        let mut world_state = WorldState::new(3).unwrap();
+        assert!(world_state.step([2, 0]).is_err());
+        world_state.step([-1, 1]).unwrap();
+        assert_eq!(world_state.nodes(), [[-1, 1], [0, 0], [0, 0]]);
+    }
+
+    #[test]
+    fn test_three_dimensions() {
+        let world_state = simulate::<3>("R 2\nF 2\nU 1\nB 3", 3).unwrap();
+
+        assert_eq!(world_state.nodes(), [[2, 1, -1], [2, 1, 0], [1, 0, 1]]);
+        assert_eq!(world_state.path(2).unwrap(), [[0, 0, 0], [1, 0, 1]]);
+
+        // a rope that never leaves the plane behaves exactly like the puzzle's
+        let world_state = simulate::<3>(TEST_PART_2_INPUT, 10).unwrap();
+        assert_eq!(world_state.num_visited(9), Some(36));
+
+        // there is no third axis to move along in the plane
+        assert!(simulate::<2>("F 1", 2).is_err());
+        assert!(simulate::<4>("B 1", 2).is_ok());
+
+        // with more axes than bits in a chunk, every knot's visited cells still fit in a chunk
+        let world_state = simulate::<40>(TEST_PART_1_INPUT, 2).unwrap();
+        assert_eq!(world_state.num_visited(1), Some(13));
+    }
+
+    #[test]
+    fn test_long_moves() {
+        let input = "R 1000\nUL 300\nD 256\nL 2000";
+        let moves = parse(input).unwrap();
+        assert_eq!(moves[0].amount, 1_000);
+
+        // sliding a straightened rope must match stepping it one cell at a time
+        let world_state: WorldState = simulate(input, 10).unwrap();
+        let mut stepped = WorldState::new(10).unwrap();
+        for this_move in moves {
+            let delta = this_move.direction.offset_in().unwrap();
+            for _ in 0..this_move.amount {
+                stepped.step(delta).unwrap();
+            }
+        }
+
+        assert_eq!(world_state.nodes(), stepped.nodes());
+        for idx in 0..10 {
+            assert_eq!(world_state.visited(idx), stepped.visited(idx));
+            assert_eq!(world_state.path(idx), stepped.path(idx));
+        }
+
+        let without_paths = WorldState::<2>::without_paths(10).unwrap();
+        assert_eq!(without_paths.path(0), None);
+    }
+
+    #[test]
+    fn test_leaving_the_world() {
+        let mut world_state = WorldState::<2>::without_paths(2).unwrap();
+        world_state
+            .apply(Move {
+                direction: Direction::Right,
+                amount: u32::MAX,
+            })
+            .unwrap_err();
+
+        // the steps taken before the rope straightened out stay, but the slide never starts
+        assert_eq!(world_state.nodes(), [[2, 0], [1, 0]]);
+    }
+
     #[test]
     fn solution_part_two() {
         let result = part_two(INPUT).unwrap();
@@ -0,0 +1,253 @@
+use anyhow::Result;
+
+use crate::json;
+
+use super::{Move, WorldState};
+
+/// The inclusive range of cells drawn in a frame, with `y` growing upwards like the simulation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Viewport {
+    pub min: (i32, i32),
+    pub max: (i32, i32),
+}
+
+impl Viewport {
+    /// The smallest viewport containing every cell any knot of `world_state` has visited.
+    pub fn fitting(world_state: &WorldState) -> Self {
+        let mut result = Self {
+            min: (0, 0),
+            max: (0, 0),
+        };
+
+        for idx in 0..world_state.num_nodes() {
+            // every index below `num_nodes` has visited at least the start
+            for position in world_state.visited(idx).unwrap().iter() {
+                result.include(position);
+            }
+        }
+
+        result
+    }
+
+    /// Grows the viewport just enough to contain `position`.
+    fn include(&mut self, [x, y]: [i32; 2]) {
+        self.min = (i32::min(self.min.0, x), i32::min(self.min.1, y));
+        self.max = (i32::max(self.max.0, x), i32::max(self.max.1, y));
+    }
+
+    pub fn width(&self) -> usize {
+        (self.max.0 - self.min.0 + 1).try_into().unwrap_or(0)
+    }
+
+    pub fn height(&self) -> usize {
+        (self.max.1 - self.min.1 + 1).try_into().unwrap_or(0)
+    }
+
+    fn render(&self, mut cell: impl FnMut((i32, i32)) -> char) -> String {
+        let mut result = String::new();
+        for y in (self.min.1..=self.max.1).rev() {
+            result.extend((self.min.0..=self.max.0).map(|x| cell((x, y))));
+            result.push('\n');
+        }
+
+        result
+    }
+}
+
+// `H` for the head, then `T` for the tail of a two-knot rope or `1`, `2`, ... for longer ones
+fn knot_label(idx: usize, num_nodes: usize) -> char {
+    match idx {
+        0 => 'H',
+        _ if num_nodes == 2 => 'T',
+        _ => u32::try_from(idx)
+            .ok()
+            .and_then(|idx| char::from_digit(idx, 36))
+            .unwrap_or('*'),
+    }
+}
+
+/// Draws the current position of every knot in the style of the puzzle text. Knots closer to the
+/// head are drawn on top of later ones, and `s` marks the starting cell when nothing covers it.
+pub fn render_frame(world_state: &WorldState, viewport: &Viewport) -> String {
+    let nodes = world_state.nodes();
+
+    viewport.render(
+        |position| match nodes.iter().position(|&[x, y]| (x, y) == position) {
+            Some(idx) => knot_label(idx, nodes.len()),
+            None if position == (0, 0) => 's',
+            None => '.',
+        },
+    )
+}
+
+/// Draws every cell knot `idx` has visited as `#`, like the puzzle's final diagrams.
+pub fn render_visited(world_state: &WorldState, viewport: &Viewport, idx: usize) -> String {
+    let visited = world_state.visited(idx);
+
+    viewport.render(|position| {
+        if position == (0, 0) {
+            's'
+        } else if visited.is_some_and(|visited| visited.contains(&[position.0, position.1])) {
+            '#'
+        } else {
+            '.'
+        }
+    })
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameInterval {
+    /// A frame after every single step of the head.
+    Step,
+    /// A frame after every full move, as in the puzzle's examples.
+    Move,
+}
+
+/// Simulates `moves` on a rope of `num_nodes` knots, drawing the starting state and then a frame
+/// at every `interval`. All frames share a viewport fitting the whole simulation.
+pub fn render_frames(
+    moves: &[Move],
+    num_nodes: usize,
+    interval: FrameInterval,
+) -> Result<Vec<String>> {
+    // a first pass only follows the knots to size the viewport, so nothing records their paths
+    let mut world_state = WorldState::without_paths(num_nodes)?;
+    let mut viewport = Viewport {
+        min: (0, 0),
+        max: (0, 0),
+    };
+    for &this_move in moves {
+        let delta = this_move.direction.offset_in()?;
+        for _ in 0..this_move.amount {
+            world_state.step(delta)?;
+            for &position in world_state.nodes() {
+                viewport.include(position);
+            }
+        }
+    }
+
+    let mut world_state = WorldState::without_paths(num_nodes)?;
+    let mut frames = vec![render_frame(&world_state, &viewport)];
+    for &this_move in moves {
+        match interval {
+            FrameInterval::Step => {
+                let delta = this_move.direction.offset_in()?;
+                for _ in 0..this_move.amount {
+                    world_state.step(delta)?;
+                    frames.push(render_frame(&world_state, &viewport));
+                }
+            }
+            FrameInterval::Move => {
+                world_state.apply(this_move)?;
+                frames.push(render_frame(&world_state, &viewport));
+            }
+        }
+    }
+
+    Ok(frames)
+}
+
+/// Packs frames into an [asciicast v2](https://docs.asciinema.org/manual/asciicast/v2/) recording,
+/// showing each one for `frame_seconds`.
+pub fn to_asciicast(frames: &[String], frame_seconds: f64) -> String {
+    let width = frames
+        .iter()
+        .flat_map(|frame| frame.lines())
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let height = frames
+        .iter()
+        .map(|frame| frame.lines().count())
+        .max()
+        .unwrap_or(0);
+
+    let mut result = format!(r#"{{"version": 2, "width": {width}, "height": {height}}}"#);
+    result.push('\n');
+
+    for (i, frame) in frames.iter().enumerate() {
+        // move the cursor home and clear the screen before drawing each frame
+        let output = format!("\u{1b}[H\u{1b}[2J{}", frame.replace('\n', "\r\n"));
+        result.push_str(&format!(r#"[{:.3}, "o", "#, i as f64 * frame_seconds));
+        json::push_string(&mut result, &output);
+        result.push_str("]\n");
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{parse, simulate, tests::TEST_PART_1_INPUT};
+    use super::*;
+
+    #[test]
+    fn test_render_frames() {
+        let moves = parse(TEST_PART_1_INPUT).unwrap();
+        let frames = render_frames(&moves, 2, FrameInterval::Move).unwrap();
+
+        assert_eq!(frames.len(), moves.len() + 1);
+        assert_eq!(frames[0], "......\n......\n......\n......\nH.....\n");
+        assert_eq!(frames[1], "......\n......\n......\n......\ns..TH.\n");
+        assert_eq!(frames[2], "....H.\n....T.\n......\n......\ns.....\n");
+
+        let steps = render_frames(&moves, 2, FrameInterval::Step).unwrap();
+        assert_eq!(steps.len(), 1 + 4 + 4 + 3 + 1 + 4 + 1 + 5 + 2);
+        assert_eq!(steps[1], "......\n......\n......\n......\nTH....\n");
+        assert_eq!(steps.last(), frames.last());
+    }
+
+    #[test]
+    fn test_render_frames_viewport() {
+        let input = "R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20";
+        let moves = parse(input).unwrap();
+        let frames = render_frames(&moves, 10, FrameInterval::Move).unwrap();
+
+        let viewport = Viewport::fitting(&simulate(input, 10).unwrap());
+        let lines = frames[0].lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), viewport.height());
+        assert!(lines.iter().all(|line| line.len() == viewport.width()));
+    }
+
+    #[test]
+    fn test_render_long_rope() {
+        let world_state = simulate("R 4\nU 4", 10).unwrap();
+        let viewport = Viewport {
+            min: (0, 0),
+            max: (5, 4),
+        };
+
+        let expected = "....H.
+....1.
+..432.
+.5....
+6.....
+";
+        assert_eq!(render_frame(&world_state, &viewport), expected);
+    }
+
+    #[test]
+    fn test_render_visited() {
+        let world_state = simulate(TEST_PART_1_INPUT, 2).unwrap();
+        let viewport = Viewport::fitting(&world_state);
+
+        let expected = "..##..
+...##.
+.####.
+....#.
+s###..
+";
+        assert_eq!(render_visited(&world_state, &viewport, 1), expected);
+    }
+
+    #[test]
+    fn test_asciicast() {
+        let frames = ["H.\n".to_string(), "sH\n".to_string()];
+
+        let expected = r#"{"version": 2, "width": 2, "height": 1}
+[0.000, "o", "\u001b[H\u001b[2JH.\r\n"]
+[0.500, "o", "\u001b[H\u001b[2JsH\r\n"]
+"#;
+        assert_eq!(to_asciicast(&frames, 0.5), expected);
+    }
+}
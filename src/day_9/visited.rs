@@ -0,0 +1,208 @@
+use std::collections::HashMap;
+
+use super::Position;
+
+/// A set of cells stored as bitmaps over fixed-size chunks of space, so that long walks through
+/// mostly neighboring cells take a bit per cell rather than a hashed entry.
+#[derive(Clone, Debug, Default)]
+pub struct VisitedCells<const D: usize> {
+    chunk_idx: HashMap<Position<D>, usize>,
+    chunks: Vec<(Position<D>, Box<[u64]>)>,
+    // walks mostly stay within a chunk, so remember the last one to skip hashing its key again
+    last_chunk: Option<(Position<D>, usize)>,
+    len: usize,
+}
+
+// every chunk is `2^CHUNK_BITS` cells, whatever the number of axes
+const CHUNK_BITS: usize = 12;
+
+impl<const D: usize> VisitedCells<D> {
+    // chunks are `2^CHUNK_SHIFTS[axis]` cells along each axis, sharing out `CHUNK_BITS` as evenly
+    // as possible with any left over going to the first axes, so with more than `CHUNK_BITS` axes
+    // the later ones are a single cell wide
+    const CHUNK_SHIFTS: [usize; D] = {
+        let mut result = [0; D];
+        let mut axis = 0;
+        while axis < D {
+            result[axis] = CHUNK_BITS / D + if axis < CHUNK_BITS % D { 1 } else { 0 };
+            axis += 1;
+        }
+
+        result
+    };
+
+    pub fn new() -> Self {
+        Self {
+            chunk_idx: HashMap::new(),
+            chunks: Vec::new(),
+            last_chunk: None,
+            len: 0,
+        }
+    }
+
+    fn words_per_chunk() -> usize {
+        let bits = Self::CHUNK_SHIFTS.iter().sum::<usize>();
+        (1_usize << bits).div_ceil(64)
+    }
+
+    fn chunk_mask(axis: usize) -> i32 {
+        (1 << Self::CHUNK_SHIFTS[axis]) - 1
+    }
+
+    // the chunk a cell is in, and the index of its bit within that chunk
+    fn locate(position: &Position<D>) -> (Position<D>, usize) {
+        let mut chunk = [0; D];
+        let mut bit = 0;
+        for axis in (0..D).rev() {
+            // arithmetic shifts round towards negative infinity, so negative cells chunk correctly
+            let shift = Self::CHUNK_SHIFTS[axis];
+            chunk[axis] = position[axis] >> shift;
+            bit = (bit << shift) | (position[axis] & Self::chunk_mask(axis)) as usize;
+        }
+
+        (chunk, bit)
+    }
+
+    /// Marks `position` as visited, returning whether it wasn't already.
+    pub fn insert(&mut self, position: Position<D>) -> bool {
+        let (chunk, bit) = Self::locate(&position);
+        let idx = match self.last_chunk {
+            Some((last_chunk, idx)) if last_chunk == chunk => idx,
+            _ => {
+                let chunks = &mut self.chunks;
+                let idx = *self.chunk_idx.entry(chunk).or_insert_with(|| {
+                    chunks.push((chunk, vec![0; Self::words_per_chunk()].into_boxed_slice()));
+                    chunks.len() - 1
+                });
+                self.last_chunk = Some((chunk, idx));
+                idx
+            }
+        };
+
+        let word = &mut self.chunks[idx].1[bit / 64];
+        let mask = 1 << (bit % 64);
+        let inserted = *word & mask == 0;
+        *word |= mask;
+
+        self.len += usize::from(inserted);
+        inserted
+    }
+
+    pub fn contains(&self, position: &Position<D>) -> bool {
+        let (chunk, bit) = Self::locate(position);
+
+        self.chunk_idx
+            .get(&chunk)
+            .is_some_and(|&idx| self.chunks[idx].1[bit / 64] & (1 << (bit % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Every visited cell, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = Position<D>> + '_ {
+        self.chunks.iter().flat_map(|(chunk, words)| {
+            words.iter().enumerate().flat_map(move |(word_idx, &word)| {
+                let mut remaining = word;
+                std::iter::from_fn(move || {
+                    if remaining == 0 {
+                        return None;
+                    }
+
+                    let bit = word_idx * 64 + remaining.trailing_zeros() as usize;
+                    remaining &= remaining - 1;
+
+                    let mut position = [0; D];
+                    let mut remaining_bit = bit;
+                    for (axis, (component, &chunk_component)) in
+                        position.iter_mut().zip(chunk).enumerate()
+                    {
+                        let shift = Self::CHUNK_SHIFTS[axis];
+                        let offset = remaining_bit as i32 & Self::chunk_mask(axis);
+                        remaining_bit >>= shift;
+                        *component = (chunk_component << shift) | offset;
+                    }
+
+                    Some(position)
+                })
+            })
+        })
+    }
+}
+
+// chunks are stored in the order they were first visited, so compare the cells themselves
+impl<const D: usize> PartialEq for VisitedCells<D> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().all(|position| other.contains(&position))
+    }
+}
+
+impl<const D: usize> Eq for VisitedCells<D> {}
+
+impl<const D: usize> FromIterator<Position<D>> for VisitedCells<D> {
+    fn from_iter<I: IntoIterator<Item = Position<D>>>(iter: I) -> Self {
+        let mut result = Self::new();
+        for position in iter {
+            result.insert(position);
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_insert_and_contains() {
+        let mut cells = VisitedCells::<2>::new();
+
+        assert!(cells.is_empty());
+        assert!(cells.insert([0, 0]));
+        assert!(!cells.insert([0, 0]));
+        assert!(cells.insert([-1, -1]));
+        assert!(cells.insert([64, -65]));
+
+        assert_eq!(cells.len(), 3);
+        assert!(cells.contains(&[-1, -1]));
+        assert!(cells.contains(&[64, -65]));
+        assert!(!cells.contains(&[-1, 0]));
+        assert!(!cells.contains(&[1_000, 1_000]));
+    }
+
+    #[test]
+    fn test_iter() {
+        let positions = (-40..40)
+            .flat_map(|x| [[x, x * 3, -x], [x, 7, x * x]])
+            .collect::<HashSet<_>>();
+        let cells = positions.iter().copied().collect::<VisitedCells<3>>();
+
+        assert_eq!(cells.len(), positions.len());
+        assert_eq!(cells.iter().collect::<HashSet<_>>(), positions);
+    }
+
+    #[test]
+    fn test_many_axes() {
+        assert_eq!(VisitedCells::<5>::CHUNK_SHIFTS, [3, 3, 2, 2, 2]);
+        assert_eq!(VisitedCells::<80>::CHUNK_SHIFTS.iter().sum::<usize>(), 12);
+        assert_eq!(VisitedCells::<80>::words_per_chunk(), 64);
+
+        let positions = (-40..40)
+            .map(|x| std::array::from_fn(|axis| x * (axis as i32 % 7 - 3)))
+            .collect::<HashSet<_>>();
+        let cells = positions.iter().copied().collect::<VisitedCells<80>>();
+
+        assert_eq!(cells.len(), positions.len());
+        assert!(cells.contains(&[0; 80]));
+        assert!(!cells.contains(&[1; 80]));
+        assert_eq!(cells.iter().collect::<HashSet<_>>(), positions);
+    }
+}
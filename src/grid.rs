@@ -0,0 +1,348 @@
+use std::ops::{Index, IndexMut};
+
+use anyhow::{anyhow, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    /// The `(dx, dy)` of a single step in this direction. `y` grows downwards, so `Up` is `(0, -1)`.
+    pub fn offset(self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (1, -1),
+            Direction::DownLeft => (-1, 1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+}
+
+/// A rectangular grid stored row-major in a single `Vec`, indexed by `(x, y)` with `(0, 0)` in
+/// the top left corner.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// A `width` by `height` grid with every cell set to `fill`. The number of cells has to fit
+    /// in a `usize`, which it always does for the size of a grid that already exists; otherwise
+    /// this panics.
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        let num_cells = width
+            .checked_mul(height)
+            .unwrap_or_else(|| panic!("a {width}x{height} grid has too many cells"));
+
+        Self {
+            width,
+            height,
+            cells: vec![fill; num_cells],
+        }
+    }
+
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Result<Self> {
+        let num_cells = width
+            .checked_mul(height)
+            .ok_or_else(|| anyhow!("a {}x{} grid has too many cells", width, height))?;
+        if cells.len() != num_cells {
+            return Err(anyhow!(
+                "expected {} cells for a {}x{} grid, but got {}",
+                num_cells,
+                width,
+                height,
+                cells.len()
+            ));
+        }
+
+        Ok(Self {
+            width,
+            height,
+            cells,
+        })
+    }
+
+    /// Builds a grid from a list of rows, which must all be the same length.
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self> {
+        let height = rows.len();
+        let width = rows.first().map_or(0, Vec::len);
+
+        if let Some((y, row)) = rows.iter().enumerate().find(|(_, row)| row.len() != width) {
+            return Err(anyhow!(
+                "expected every row to have {} cells, but row {} has {}",
+                width,
+                y,
+                row.len()
+            ));
+        }
+
+        Self::from_vec(width, height, rows.into_iter().flatten().collect())
+    }
+
+    /// Parses one row per line of `input`, converting every character with `parse_cell`.
+    pub fn parse_with(input: &str, mut parse_cell: impl FnMut(char) -> Result<T>) -> Result<Self> {
+        let rows = input
+            .lines()
+            .map(|line| {
+                line.chars()
+                    .map(&mut parse_cell)
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::from_rows(rows)
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        let idx = self.idx(position)?;
+        Some(&self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        let idx = self.idx(position)?;
+        Some(&mut self.cells[idx])
+    }
+
+    fn idx(&self, position @ (x, y): (usize, usize)) -> Option<usize> {
+        self.contains(position).then_some(y * self.width + x)
+    }
+
+    /// The position one step from `position` in `direction`, if it's still inside the grid.
+    pub fn step(&self, (x, y): (usize, usize), direction: Direction) -> Option<(usize, usize)> {
+        let (dx, dy) = direction.offset();
+        let next = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+
+        self.contains(next).then_some(next)
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every cell with its position, in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(&self.cells)
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> {
+        // `chunks` panics on a zero chunk size, and a zero-width grid has no cells anyway
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn row(&self, y: usize) -> impl DoubleEndedIterator<Item = &T> {
+        self.rows().nth(y).into_iter().flatten()
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> {
+        let cells = if x < self.width {
+            &self.cells[x..]
+        } else {
+            &[]
+        };
+
+        cells.iter().step_by(self.width.max(1))
+    }
+
+    /// Every cell from `start` (exclusive) to the edge of the grid in `direction`, nearest first.
+    pub fn ray(
+        &self,
+        start: (usize, usize),
+        direction: Direction,
+    ) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let mut current = Some(start).filter(|&start| self.contains(start));
+
+        std::iter::from_fn(move || {
+            let next = self.step(current?, direction);
+            current = next;
+            next.map(|position| (position, &self[position]))
+        })
+    }
+
+    /// Adjacent cells in each of `directions` that are inside the grid, e.g. [`Direction::CARDINAL`]
+    /// or [`Direction::ALL`].
+    pub fn neighbors<'a>(
+        &'a self,
+        position: (usize, usize),
+        directions: &'a [Direction],
+    ) -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
+        directions
+            .iter()
+            .filter_map(move |&direction| self.step(position, direction))
+            .map(|neighbor| (neighbor, &self[neighbor]))
+    }
+
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells: self.cells.iter().map(f).collect(),
+        }
+    }
+}
+
+impl Grid<u8> {
+    /// Parses a map of single decimal digits.
+    pub fn parse_digits(input: &str) -> Result<Self> {
+        Self::parse_with(input, |c| {
+            c.to_digit(10)
+                .map(|digit| digit as u8)
+                .ok_or_else(|| anyhow!("expected a digit, but got {:?}", c))
+        })
+    }
+}
+
+impl Grid<char> {
+    pub fn parse_chars(input: &str) -> Result<Self> {
+        Self::parse_with(input, Ok)
+    }
+}
+
+impl<T, const W: usize, const H: usize> From<[[T; W]; H]> for Grid<T> {
+    fn from(rows: [[T; W]; H]) -> Self {
+        Self {
+            width: W,
+            height: H,
+            cells: rows.into_iter().flatten().collect(),
+        }
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &Self::Output {
+        self.get(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside of a {}x{} grid",
+                position, self.width, self.height
+            )
+        })
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, position: (usize, usize)) -> &mut Self::Output {
+        let (width, height) = (self.width, self.height);
+        self.get_mut(position).unwrap_or_else(|| {
+            panic!(
+                "position {:?} is outside of a {}x{} grid",
+                position, width, height
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = "123
+456";
+
+    #[test]
+    fn test_parse_and_index() {
+        let grid = Grid::parse_digits(TEST_INPUT).unwrap();
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[(0, 0)], 1);
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get((0, 2)), None);
+        assert_eq!(grid, Grid::from([[1, 2, 3], [4, 5, 6]]));
+
+        assert!(Grid::parse_digits("12\n3").is_err());
+        assert!(Grid::<u8>::from_vec(usize::MAX, 2, vec![]).is_err());
+
+        // ragged rows are rejected before room is made for every row to be as wide as the first
+        let mut rows = vec![Vec::new(); 100_000];
+        rows[0] = vec![0u8; 100_000];
+        assert!(Grid::from_rows(rows).is_err());
+        assert!(Grid::parse_digits("1a").is_err());
+        assert_eq!(Grid::parse_chars("ab\ncd").unwrap()[(1, 1)], 'd');
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = Grid::parse_digits(TEST_INPUT).unwrap();
+
+        assert_eq!(grid.row(1).copied().collect::<Vec<_>>(), [4, 5, 6]);
+        assert_eq!(grid.column(2).copied().collect::<Vec<_>>(), [3, 6]);
+        assert_eq!(grid.column(1).rev().copied().collect::<Vec<_>>(), [5, 2]);
+        assert_eq!(grid.row(2).count(), 0);
+        assert_eq!(grid.column(3).count(), 0);
+    }
+
+    #[test]
+    fn test_rays_and_neighbors() {
+        let grid = Grid::parse_digits(TEST_INPUT).unwrap();
+
+        let ray = |start, direction| {
+            grid.ray(start, direction)
+                .map(|(_, &cell)| cell)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ray((0, 0), Direction::Right), [2, 3]);
+        assert_eq!(ray((2, 1), Direction::Left), [5, 4]);
+        assert_eq!(ray((0, 0), Direction::DownRight), [5]);
+        assert_eq!(ray((0, 0), Direction::Up), []);
+
+        let neighbors = |directions| {
+            let mut result = grid
+                .neighbors((1, 0), directions)
+                .map(|(_, &cell)| cell)
+                .collect::<Vec<_>>();
+            result.sort_unstable();
+            result
+        };
+        assert_eq!(neighbors(&Direction::CARDINAL), [1, 3, 5]);
+        assert_eq!(neighbors(&Direction::ALL), [1, 3, 4, 5, 6]);
+    }
+}
@@ -0,0 +1,31 @@
+/// Appends `value` to `result` as a quoted JSON string, escaping quotes, backslashes and control
+/// characters.
+pub(crate) fn push_string(result: &mut String, value: &str) {
+    result.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => result.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_string() {
+        let mut result = String::from("[");
+        push_string(&mut result, "a \"b\"\\c\n\r\t\u{1b}[H é");
+        result.push(']');
+
+        assert_eq!(result, r#"["a \"b\"\\c\n\r\t\u001b[H é"]"#);
+    }
+}
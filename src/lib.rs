@@ -9,3 +9,8 @@ pub mod day_6;
 pub mod day_7;
 pub mod day_8;
 pub mod day_9;
+pub mod grid;
+mod json;
+pub mod ocr;
+#[cfg(test)]
+mod testing;
@@ -0,0 +1,273 @@
+use std::ops::Range;
+
+use anyhow::{anyhow, Result};
+
+use crate::grid::Grid;
+
+/// The two block letter fonts puzzles draw their answers in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Font {
+    /// Letters 4 pixels wide and 6 tall, as drawn by e.g. 2022 day 10's CRT.
+    Small,
+    /// Letters 6 pixels wide and 10 tall, as in 2018 day 10.
+    Large,
+}
+
+impl Font {
+    pub fn height(self) -> usize {
+        match self {
+            Font::Small => 6,
+            Font::Large => 10,
+        }
+    }
+
+    fn glyphs(self) -> &'static [(char, &'static str)] {
+        match self {
+            Font::Small => SMALL_GLYPHS,
+            Font::Large => LARGE_GLYPHS,
+        }
+    }
+}
+
+/// Reads the letters drawn on `screen`, choosing the font by the screen's height.
+pub fn recognize(screen: &Grid<bool>) -> Result<String> {
+    let font = [Font::Small, Font::Large]
+        .into_iter()
+        .find(|font| font.height() == screen.height())
+        .ok_or_else(|| {
+            anyhow!(
+                "no font is {} pixels tall, expected a screen 6 or 10 pixels tall",
+                screen.height()
+            )
+        })?;
+
+    recognize_with(screen, font)
+}
+
+/// Reads the letters drawn on `screen` in `font`. Letters are separated by blank columns, and every
+/// one of them has to be recognized.
+pub fn recognize_with(screen: &Grid<bool>, font: Font) -> Result<String> {
+    if screen.height() != font.height() {
+        return Err(anyhow!(
+            "expected a screen {} pixels tall for {:?} letters, but it is {} pixels tall",
+            font.height(),
+            font,
+            screen.height()
+        ));
+    }
+
+    let mut result = String::new();
+    let mut unrecognized = Vec::new();
+    for columns in glyph_columns(screen) {
+        let letter = font
+            .glyphs()
+            .iter()
+            .find(|(_, glyph)| glyph_matches(screen, columns.clone(), glyph))
+            .map(|&(letter, _)| letter);
+
+        match letter {
+            Some(letter) => result.push(letter),
+            None => unrecognized.push(columns),
+        }
+    }
+
+    if !unrecognized.is_empty() {
+        let columns = unrecognized
+            .iter()
+            .map(|columns| format!("{}..{}", columns.start, columns.end))
+            .collect::<Vec<_>>();
+
+        return Err(anyhow!(
+            "couldn't recognize the {:?} letters in columns {}, read {:?} from the rest",
+            font,
+            columns.join(", "),
+            result
+        ));
+    }
+
+    Ok(result)
+}
+
+// ranges of columns with at least one lit pixel, separated by blank columns
+fn glyph_columns(screen: &Grid<bool>) -> Vec<Range<usize>> {
+    let mut result = Vec::new();
+    let mut start = None;
+    for x in 0..=screen.width() {
+        let is_blank = x == screen.width() || !screen.column(x).any(|&lit| lit);
+
+        match (start, is_blank) {
+            (None, false) => start = Some(x),
+            (Some(glyph_start), true) => {
+                result.push(glyph_start..x);
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    result
+}
+
+fn glyph_matches(screen: &Grid<bool>, columns: Range<usize>, glyph: &str) -> bool {
+    let rows = glyph.lines().collect::<Vec<_>>();
+
+    // glyphs are drawn with blank columns around them, which the screen's columns don't include
+    let first = (0..rows[0].len()).find(|&x| rows.iter().any(|row| row.as_bytes()[x] == b'#'));
+    let last = (0..rows[0].len()).rfind(|&x| rows.iter().any(|row| row.as_bytes()[x] == b'#'));
+    let (Some(first), Some(last)) = (first, last) else {
+        return false;
+    };
+
+    last + 1 - first == columns.len()
+        && rows.iter().enumerate().all(|(y, row)| {
+            columns
+                .clone()
+                .zip(&row.as_bytes()[first..=last])
+                .all(|(x, &pixel)| screen[(x, y)] == (pixel == b'#'))
+        })
+}
+
+const SMALL_GLYPHS: &[(char, &str)] = &[
+    ('A', ".##.\n#..#\n#..#\n####\n#..#\n#..#"),
+    ('B', "###.\n#..#\n###.\n#..#\n#..#\n###."),
+    ('C', ".##.\n#..#\n#...\n#...\n#..#\n.##."),
+    ('E', "####\n#...\n###.\n#...\n#...\n####"),
+    ('F', "####\n#...\n###.\n#...\n#...\n#..."),
+    ('G', ".##.\n#..#\n#...\n#.##\n#..#\n.###"),
+    ('H', "#..#\n#..#\n####\n#..#\n#..#\n#..#"),
+    ('I', ".###\n..#.\n..#.\n..#.\n..#.\n.###"),
+    ('J', "..##\n...#\n...#\n...#\n#..#\n.##."),
+    ('K', "#..#\n#.#.\n##..\n#.#.\n#.#.\n#..#"),
+    ('L', "#...\n#...\n#...\n#...\n#...\n####"),
+    ('O', ".##.\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('P', "###.\n#..#\n#..#\n###.\n#...\n#..."),
+    ('R', "###.\n#..#\n#..#\n###.\n#.#.\n#..#"),
+    ('S', ".###\n#...\n#...\n.##.\n...#\n###."),
+    ('U', "#..#\n#..#\n#..#\n#..#\n#..#\n.##."),
+    ('Y', "#...#\n#...#\n.#.#.\n..#..\n..#..\n..#.."),
+    ('Z', "####\n...#\n..#.\n.#..\n#...\n####"),
+];
+
+const LARGE_GLYPHS: &[(char, &str)] = &[
+    (
+        'A',
+        "..##..\n.#..#.\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'B',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#....#\n#....#\n#....#\n#....#\n#####.",
+    ),
+    (
+        'C',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#....#\n.####.",
+    ),
+    (
+        'E',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'F',
+        "######\n#.....\n#.....\n#.....\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'G',
+        ".####.\n#....#\n#.....\n#.....\n#.....\n#..###\n#....#\n#....#\n#...##\n.###.#",
+    ),
+    (
+        'H',
+        "#....#\n#....#\n#....#\n#....#\n######\n#....#\n#....#\n#....#\n#....#\n#....#",
+    ),
+    (
+        'J',
+        "...###\n....#.\n....#.\n....#.\n....#.\n....#.\n....#.\n#...#.\n#...#.\n.###..",
+    ),
+    (
+        'K',
+        "#....#\n#...#.\n#..#..\n#.#...\n##....\n##....\n#.#...\n#..#..\n#...#.\n#....#",
+    ),
+    (
+        'L',
+        "#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n#.....\n######",
+    ),
+    (
+        'N',
+        "#....#\n##...#\n##...#\n#.#..#\n#.#..#\n#..#.#\n#..#.#\n#...##\n#...##\n#....#",
+    ),
+    (
+        'P',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#.....\n#.....\n#.....\n#.....\n#.....",
+    ),
+    (
+        'R',
+        "#####.\n#....#\n#....#\n#....#\n#####.\n#..#..\n#...#.\n#...#.\n#....#\n#....#",
+    ),
+    (
+        'X',
+        "#....#\n#....#\n.#..#.\n.#..#.\n..##..\n..##..\n.#..#.\n.#..#.\n#....#\n#....#",
+    ),
+    (
+        'Z',
+        "######\n.....#\n.....#\n....#.\n...#..\n..#...\n.#....\n#.....\n#.....\n######",
+    ),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen(art: &str) -> Grid<bool> {
+        Grid::parse_with(art, |c| Ok(c == '#')).unwrap()
+    }
+
+    #[test]
+    fn test_small_font() {
+        let screen = screen(
+            "\
+.##..###..####.#..#.####.
+#..#.#..#.#....#..#.#....
+#..#.###..###..####.###..
+####.#..#.#....#..#.#....
+#..#.#..#.#....#..#.#....
+#..#.###..####.#..#.####.",
+        );
+
+        assert_eq!(recognize(&screen).unwrap(), "ABEHE");
+    }
+
+    #[test]
+    fn test_large_font() {
+        let screen = screen(
+            "\
+#....#..#####.
+#....#..#....#
+.#..#...#....#
+.#..#...#....#
+..##....#####.
+..##....#.....
+.#..#...#.....
+.#..#...#.....
+#....#..#.....
+#....#..#.....",
+        );
+
+        assert_eq!(recognize(&screen).unwrap(), "XP");
+        assert!(recognize_with(&screen, Font::Small).is_err());
+    }
+
+    #[test]
+    fn test_unrecognized_glyphs() {
+        let screen = screen(
+            "\
+####..#..####
+...#.###.#...
+..#...#..###.
+.#.......#...
+#........#...
+####.....#...",
+        );
+
+        let err = recognize(&screen).unwrap_err().to_string();
+        assert!(err.contains("columns 5..8"), "{}", err);
+        assert!(err.contains("\"ZF\""), "{}", err);
+    }
+}
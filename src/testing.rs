@@ -0,0 +1,14 @@
+use std::fmt::Debug;
+
+use anyhow::Result;
+
+/// Solves both parts of a model that was built by hand rather than parsed, checking the answers.
+pub(crate) fn assert_solves<M: ?Sized, A: PartialEq + Debug, B: PartialEq + Debug>(
+    model: &M,
+    solve_part_one: impl Fn(&M) -> Result<A>,
+    solve_part_two: impl Fn(&M) -> Result<B>,
+    (expected_one, expected_two): (A, B),
+) {
+    assert_eq!(solve_part_one(model).unwrap(), expected_one);
+    assert_eq!(solve_part_two(model).unwrap(), expected_two);
+}